@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use url::Url;
+
+use crate::{HashMode, Page};
+
+/// Creates a page for every HTML file under `dir`, with URLs relative to `base_url`.
+pub(crate) fn pages(dir: &Path, base_url: &Url) -> Result<Vec<Page>, String> {
+    let mut pages = vec![];
+    for path in html_files(dir)? {
+        let relative = path
+            .strip_prefix(dir)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let url = url(relative, base_url)?;
+
+        let contents = std::fs::read(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+        let mut page = Page::new(url)
+            .with_lastmod(DateTime::<Utc>::from(modified).fixed_offset())
+            .with_hash(HashMode::default().fingerprint(&String::from_utf8_lossy(&contents)));
+        page.content_length = Some(contents.len());
+        pages.push(page);
+    }
+
+    Ok(pages)
+}
+
+/// Returns the paths of the `.html` and `.htm` files under `dir`, in any order.
+/// Extensions are matched case-insensitively and symbolic links are not followed.
+fn html_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| format!("failed to read {}: {}", dir.display(), e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("failed to read {}: {}", dir.display(), e))?;
+            let file_type = entry
+                .file_type()
+                .map_err(|e| format!("failed to read {}: {}", entry.path().display(), e))?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                dirs.push(path);
+            } else if file_type.is_file()
+                && path.extension().is_some_and(|extension| {
+                    extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm")
+                })
+            {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Maps a file path relative to the site root to its URL.
+/// `index.html` stands for the directory it is in, e.g. `about/index.html` maps to `/about/`.
+fn url(relative: &Path, base_url: &Url) -> Result<Url, String> {
+    let mut segments = relative
+        .iter()
+        .map(|segment| {
+            segment
+                .to_str()
+                .ok_or_else(|| format!("file name is not valid UTF-8: {}", relative.display()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if segments.last().is_some_and(|name| {
+        name.eq_ignore_ascii_case("index.html") || name.eq_ignore_ascii_case("index.htm")
+    }) {
+        segments.pop();
+        segments.push("");
+    }
+
+    let mut url = base_url.clone();
+    url.path_segments_mut()
+        .map_err(|_| format!("base URL cannot have a path: {}", base_url))?
+        .pop_if_empty()
+        .extend(segments);
+    Ok(url)
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use url::Url;
+
+/// Image on a page, listed using Google's image sitemap extension.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageEntry {
+    /// Image URL.
+    pub loc: Url,
+    /// Title of the image.
+    pub title: Option<String>,
+    /// Caption of the image.
+    pub caption: Option<String>,
+}
+
+/// Video on a page, listed using Google's video sitemap extension.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VideoEntry {
+    /// URL of the video thumbnail.
+    pub thumbnail_loc: Option<Url>,
+    /// Title of the video.
+    pub title: String,
+    /// Description of the video.
+    pub description: String,
+    /// URL of the video file.
+    pub content_loc: Option<Url>,
+    /// URL of the video player.
+    pub player_loc: Option<Url>,
+    /// Duration of the video in seconds.
+    pub duration: Option<u32>,
+}
+
+/// News article, listed using Google's news sitemap extension.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NewsEntry {
+    /// Name of the news publication.
+    pub publication_name: String,
+    /// Language of the publication as an ISO 639 code.
+    pub publication_language: String,
+    /// Publication date of the article.
+    pub publication_date: DateTime<Utc>,
+    /// Title of the article.
+    pub title: String,
+}
+
+/// Alternate version of a page, listed as `<xhtml:link rel="alternate">`,
+/// e.g. a translation or a mobile version.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alternate {
+    /// URL of the alternate version.
+    pub href: Url,
+    /// Language of the alternate version, e.g. `de` or `x-default`.
+    pub hreflang: Option<String>,
+    /// Media the alternate version is meant for, e.g. `only screen and (max-width: 640px)`.
+    pub media: Option<String>,
+}
+
+/// `<xhtml:meta>` element of a page.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Meta {
+    /// Value of the `name` attribute.
+    pub name: String,
+    /// Value of the `content` attribute.
+    pub content: String,
+}
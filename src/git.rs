@@ -0,0 +1,35 @@
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{DateTime, FixedOffset};
+
+/// Returns the date of the last commit that touched `path` in the repository at `repo_path`.
+/// Returns `None` if the file has no history.
+pub(crate) fn last_commit_date(
+    repo_path: &Path,
+    path: &Path,
+) -> Result<Option<DateTime<FixedOffset>>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(["log", "-1", "--format=%cI", "--"])
+        .arg(path)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git log failed for {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let date = String::from_utf8_lossy(&output.stdout);
+    let date = date.trim();
+    if date.is_empty() {
+        return Ok(None);
+    }
+    DateTime::parse_from_rfc3339(date)
+        .map(Some)
+        .map_err(|e| format!("failed to parse commit date `{}`: {}", date, e))
+}
@@ -0,0 +1,82 @@
+/// How the contents of a page are hashed to detect changes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum HashMode {
+    /// Hashes the HTML as is, apart from surrounding whitespace and line endings.
+    #[default]
+    Raw,
+    /// Collapses every run of whitespace into a single space before hashing,
+    /// so that reformatting, e.g. reindentation, does not change the hash.
+    NormalizeWhitespace,
+    /// Removes `<script>` and `<style>` elements, and optionally comments, before hashing,
+    /// so that e.g. analytics snippets and nonces do not change the hash.
+    StripScripts {
+        /// Whether to also remove `<!-- comments -->`.
+        strip_comments: bool,
+    },
+}
+
+impl HashMode {
+    /// Computes the MD5 hash of `html` as a lowercase hexadecimal string.
+    pub fn fingerprint(&self, html: &str) -> String {
+        let contents = match self {
+            HashMode::Raw => html.trim().replace("\r\n", "\n"), // normalize line endings
+            HashMode::NormalizeWhitespace => html.split_whitespace().collect::<Vec<_>>().join(" "),
+            HashMode::StripScripts { strip_comments } => {
+                let mut html = strip_elements(html, "script");
+                html = strip_elements(&html, "style");
+                if *strip_comments {
+                    html = strip_between(&html, "<!--", "-->");
+                }
+                html.trim().replace("\r\n", "\n")
+            }
+        };
+
+        format!("{:x}", md5::compute(contents))
+    }
+}
+
+/// Removes every `<tag>` element together with its contents, ignoring case.
+fn strip_elements(html: &str, tag: &str) -> String {
+    let lowercase = html.to_ascii_lowercase();
+    let start = format!("<{}", tag);
+    let end = format!("</{}>", tag);
+    let mut stripped = String::with_capacity(html.len());
+    let mut i = 0;
+
+    while let Some(offset) = lowercase[i..].find(&start) {
+        let element_start = i + offset;
+        let after_name = element_start + start.len();
+        let is_element = lowercase[after_name..]
+            .starts_with(|c: char| c == '>' || c == '/' || c.is_ascii_whitespace());
+        stripped.push_str(&html[i..element_start]);
+        if !is_element {
+            stripped.push_str(&html[element_start..after_name]);
+            i = after_name;
+            continue;
+        }
+        i = match lowercase[after_name..].find(&end) {
+            Some(offset) => after_name + offset + end.len(),
+            None => html.len(),
+        };
+    }
+    stripped.push_str(&html[i..]);
+
+    stripped
+}
+
+/// Removes everything from each `start` up to and including the following `end`.
+fn strip_between(html: &str, start: &str, end: &str) -> String {
+    let mut stripped = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(offset) = rest.find(start) {
+        stripped.push_str(&rest[..offset]);
+        rest = match rest[offset + start.len()..].find(end) {
+            Some(end_offset) => &rest[offset + start.len() + end_offset + end.len()..],
+            None => "",
+        };
+    }
+    stripped.push_str(rest);
+
+    stripped
+}
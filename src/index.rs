@@ -0,0 +1,80 @@
+use chrono::{DateTime, FixedOffset};
+use url::Url;
+
+use crate::xml::{self, SitemapIndexSerde};
+use crate::{robots, SerializeOptions, Sitemap};
+
+/// Sitemap index listing several sitemaps.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SitemapIndex {
+    /// Sitemaps in the index.
+    pub sitemaps: Vec<SitemapIndexEntry>,
+}
+
+/// Sitemap listed in a sitemap index.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SitemapIndexEntry {
+    /// Location of the sitemap.
+    pub loc: Url,
+    /// Last modification date of the sitemap.
+    pub lastmod: Option<DateTime<FixedOffset>>,
+}
+
+/// Either a sitemap or a sitemap index, as returned by
+/// [`Sitemap::deserialize_any`](crate::Sitemap::deserialize_any).
+#[derive(Debug, PartialEq, Clone)]
+pub enum SitemapOrIndex {
+    /// Document with a `<urlset>` root.
+    Sitemap(Sitemap),
+    /// Document with a `<sitemapindex>` root.
+    Index(SitemapIndex),
+}
+
+/// Result of importing a sitemap with
+/// [`Sitemap::import_with_options`](crate::Sitemap::import_with_options).
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportReport {
+    /// Imported sitemap, merged from the child sitemaps in case of a sitemap index.
+    pub sitemap: Sitemap,
+    /// Child sitemaps that could not be imported, together with the error.
+    pub errors: Vec<(Url, String)>,
+    /// Child sitemaps that were not fetched because of `max_child_sitemaps`.
+    pub not_fetched: Vec<Url>,
+}
+
+/// Sitemap serialized into several documents.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SplitSitemap {
+    /// Serialized XML sitemaps, in order.
+    pub documents: Vec<Vec<u8>>,
+    /// Index listing the documents.
+    pub index: SitemapIndex,
+}
+
+impl SitemapIndex {
+    /// Deserializes from XML sitemap index.
+    pub fn deserialize<R: std::io::Read>(reader: R) -> Result<Self, String> {
+        let index_serde: SitemapIndexSerde = yaserde::de::from_reader(reader)
+            .map_err(|e| format!("failed to deserialize: {}", e))?;
+
+        Self::try_from(index_serde)
+    }
+
+    /// Serializes to XML sitemap index.
+    pub fn serialize<W: std::io::Write>(&self, writer: W) -> Result<(), String> {
+        let index_serde: SitemapIndexSerde = self.into();
+
+        xml::serialize(&index_serde, writer, &SerializeOptions::default())
+    }
+
+    /// Returns the `robots.txt` line that points to the index published at `index_url`.
+    pub fn robots_txt_line(index_url: &Url) -> String {
+        robots::directive(index_url)
+    }
+
+    /// Adds the `robots.txt` line that points to the index published at `index_url`
+    /// to the existing `robots.txt` contents, unless the line is already there.
+    pub fn robots_txt_block(robots_txt: &str, index_url: &Url) -> String {
+        robots::with_directive(robots_txt, index_url)
+    }
+}
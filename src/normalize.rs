@@ -0,0 +1,109 @@
+use url::Url;
+
+/// URL transformations applied by [`Sitemap::normalize_urls`](crate::Sitemap::normalize_urls).
+/// Every transformation is off by default.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizeOptions {
+    /// Whether paths should end with a slash.
+    /// Defaults to [`TrailingSlash::Preserve`].
+    pub trailing_slash: TrailingSlash,
+    /// Whether to lowercase the host.
+    pub lowercase_host: bool,
+    /// Whether to remove ports that are the default for the scheme, e.g. `:443` for `https`.
+    pub remove_default_port: bool,
+    /// Names of query parameters to remove, e.g. `ref`.
+    /// Names ending in `*` match by prefix, e.g. `utm_*`.
+    /// The query is dropped entirely if no parameters remain.
+    pub drop_query_params: Vec<String>,
+}
+
+/// Whether paths should end with a slash.
+/// The root path `/` is always kept as it is.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TrailingSlash {
+    /// Paths are left as they are.
+    #[default]
+    Preserve,
+    /// A slash is added to every path, e.g. `/page` becomes `/page/`,
+    /// except where the last segment has a file extension, e.g. `/feed.xml`.
+    Always,
+    /// Trailing slashes are removed, e.g. `/page/` becomes `/page`.
+    Never,
+}
+
+/// Applies the enabled transformations to `url`.
+pub(crate) fn normalize_url(url: &Url, options: &NormalizeOptions) -> Result<Url, String> {
+    let mut url = url.clone();
+
+    match options.trailing_slash {
+        TrailingSlash::Preserve => {}
+        TrailingSlash::Always if !url.path().ends_with('/') && !has_extension(&url) => {
+            let path = format!("{}/", url.path());
+            url.set_path(&path);
+        }
+        TrailingSlash::Always => {}
+        TrailingSlash::Never if url.path().len() > 1 && url.path().ends_with('/') => {
+            let path = url.path().trim_end_matches('/').to_string();
+            url.set_path(if path.is_empty() { "/" } else { &path });
+        }
+        TrailingSlash::Never => {}
+    }
+
+    if options.lowercase_host {
+        if let Some(host) = url.host_str() {
+            let host = host.to_lowercase();
+            url.set_host(Some(&host)).map_err(|e| e.to_string())?;
+        }
+    }
+
+    if options.remove_default_port && url.port().is_some() && url.port() == default_port(&url) {
+        url.set_port(None)
+            .map_err(|_| "failed to remove port".to_string())?;
+    }
+
+    drop_query_params(&mut url, &options.drop_query_params);
+
+    Ok(url)
+}
+
+/// Removes the query parameters with the given names.
+/// Names ending in `*` match by prefix.
+/// The query is dropped entirely if no parameters remain.
+pub(crate) fn drop_query_params(url: &mut Url, names: &[String]) {
+    if names.is_empty() || url.query().is_none() {
+        return;
+    }
+
+    let pairs = url
+        .query_pairs()
+        .filter(|(name, _)| {
+            !names.iter().any(|n| match n.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => n == name,
+            })
+        })
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+}
+
+/// Whether the last segment of the path has a file extension, e.g. `feed.xml`.
+fn has_extension(url: &Url) -> bool {
+    url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|segment| segment.rsplit_once('.'))
+        .is_some_and(|(name, extension)| !name.is_empty() && !extension.is_empty())
+}
+
+/// Default port of the URL's scheme.
+fn default_port(url: &Url) -> Option<u16> {
+    match url.scheme() {
+        "http" => Some(80),
+        "https" => Some(443),
+        _ => None,
+    }
+}
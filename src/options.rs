@@ -0,0 +1,148 @@
+use chrono::{DateTime, FixedOffset, Utc};
+
+/// Name of the `<xhtml:meta>` element that stores the page hash by default.
+const DEFAULT_HASH_META_NAME: &str = "auto_sitemap_md5_hash";
+
+/// Options for serializing the sitemap.
+#[derive(Debug, Clone)]
+pub struct SerializeOptions {
+    /// Precision of `lastmod` values.
+    /// Defaults to [`LastmodPrecision::Seconds`].
+    pub lastmod_precision: LastmodPrecision,
+    /// Cutoff before which `lastmod` is left out, so that old dates are not advertised.
+    /// The pages are still listed.
+    /// Defaults to `None`, i.e. every `lastmod` is written.
+    pub min_lastmod: Option<DateTime<Utc>>,
+    /// Name of the `<xhtml:meta>` element that stores the page hash.
+    /// Defaults to `auto_sitemap_md5_hash`.
+    pub hash_meta_name: String,
+    /// How the page hash is stored.
+    /// Defaults to [`HashStorage::Meta`].
+    pub hash_storage: HashStorage,
+    /// Whether to put each element on its own line, indented by nesting depth.
+    /// Without indentation, there is no whitespace between elements.
+    /// Defaults to `true`.
+    pub indent: bool,
+    /// Whether to start with the `<?xml version="1.0" encoding="utf-8"?>` declaration.
+    /// A byte order mark is never written.
+    /// Defaults to `true`.
+    pub xml_declaration: bool,
+    /// String used for each level of indentation.
+    /// Defaults to two spaces.
+    pub indent_string: String,
+    /// URL of an XSL stylesheet, referenced by an `<?xml-stylesheet?>` processing instruction
+    /// so that browsers display the sitemap in a human-friendly way.
+    /// Defaults to `None`, i.e. no stylesheet.
+    pub stylesheet: Option<String>,
+    /// Text of a comment written at the top of the document, followed by the time of writing,
+    /// e.g. `generated by auto_sitemap` gives `<!-- generated by auto_sitemap at 2023-08-13T11:30:46Z -->`.
+    /// Defaults to `None`, i.e. no comment.
+    pub generator_comment: Option<String>,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            lastmod_precision: LastmodPrecision::default(),
+            min_lastmod: None,
+            hash_meta_name: DEFAULT_HASH_META_NAME.to_string(),
+            hash_storage: HashStorage::default(),
+            indent: true,
+            xml_declaration: true,
+            indent_string: "  ".to_string(),
+            stylesheet: None,
+            generator_comment: None,
+        }
+    }
+}
+
+/// Options for deserializing the sitemap.
+#[derive(Debug, Clone)]
+pub struct DeserializeOptions {
+    /// Name of the `<xhtml:meta>` element that stores the page hash.
+    /// Hashes stored as comments are recognized by the same name.
+    /// Defaults to `auto_sitemap_md5_hash`.
+    pub hash_meta_name: String,
+    /// Whether to fail if several pages have the same URL, listing them in the error.
+    /// Defaults to `false`, i.e. all of the pages are kept.
+    pub reject_duplicates: bool,
+    /// Offset assumed for `lastmod` datetimes without one, e.g. `2023-01-15T10:00:00`.
+    /// Date-only values, e.g. `2023-01-15`, are always taken as midnight UTC.
+    /// Defaults to UTC.
+    pub naive_offset: FixedOffset,
+}
+
+impl Default for DeserializeOptions {
+    fn default() -> Self {
+        Self {
+            hash_meta_name: DEFAULT_HASH_META_NAME.to_string(),
+            reject_duplicates: false,
+            naive_offset: FixedOffset::east_opt(0).expect("zero offset is valid"),
+        }
+    }
+}
+
+/// Options for combining with an old sitemap.
+#[derive(Debug, Clone, Default)]
+pub struct CombineOptions {
+    /// Whether to keep old pages that are missing in the new sitemap,
+    /// e.g. pages that the crawler cannot reach.
+    /// Defaults to `false`, i.e. such pages are removed.
+    pub keep_missing: bool,
+}
+
+/// Options for importing a sitemap with
+/// [`Sitemap::import_with_options`](crate::Sitemap::import_with_options).
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Maximum number of child sitemaps fetched when importing a sitemap index;
+    /// the rest are listed in [`ImportReport::not_fetched`](crate::ImportReport::not_fetched).
+    /// Defaults to [`MAX_URLS`](crate::MAX_URLS), the most an index may list.
+    pub max_child_sitemaps: usize,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            max_child_sitemaps: crate::MAX_URLS,
+        }
+    }
+}
+
+/// Options for generating an RSS feed with [`Sitemap::to_rss`](crate::Sitemap::to_rss).
+#[derive(Debug, Clone)]
+pub struct RssOptions {
+    /// Title of the feed.
+    pub title: String,
+    /// URL of the website the feed belongs to.
+    pub link: url::Url,
+    /// Description of the feed.
+    pub description: String,
+    /// Maximum number of pages in the feed.
+    pub max_items: usize,
+}
+
+/// How the page hash is stored in `<url>`.
+/// Either way, it is read back when deserializing.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum HashStorage {
+    /// `<xhtml:meta name="auto_sitemap_md5_hash" content="..."/>`.
+    #[default]
+    Meta,
+    /// `<!-- auto_sitemap_md5_hash: ... -->`, for validators that reject elements
+    /// outside the sitemap schema.
+    /// Other `<xhtml:meta>` elements, e.g. the one storing the `ETag`, are still written.
+    Comment,
+}
+
+/// Precision with which `lastmod` values are written.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum LastmodPrecision {
+    /// Date only, e.g. `2023-08-13`.
+    DateOnly,
+    /// Date and time to the second, e.g. `2023-08-13T11:30:46Z`.
+    #[default]
+    Seconds,
+    /// Date and time to the millisecond, e.g. `2023-08-13T11:30:46.123Z`.
+    Millis,
+}
@@ -0,0 +1,26 @@
+use url::Url;
+
+/// Formats the `robots.txt` directive pointing to the sitemap at `sitemap_url`.
+pub(crate) fn directive(sitemap_url: &Url) -> String {
+    format!("Sitemap: {}", sitemap_url)
+}
+
+/// Appends the `Sitemap:` directive to `robots_txt` unless it is already there.
+pub(crate) fn with_directive(robots_txt: &str, sitemap_url: &Url) -> String {
+    let exists = robots_txt.lines().any(|line| {
+        line.split_once(':').is_some_and(|(field, value)| {
+            field.trim().eq_ignore_ascii_case("sitemap") && value.trim() == sitemap_url.as_str()
+        })
+    });
+    if exists {
+        return robots_txt.to_string();
+    }
+
+    let mut block = robots_txt.to_string();
+    if !block.is_empty() && !block.ends_with('\n') {
+        block.push('\n');
+    }
+    block.push_str(&directive(sitemap_url));
+    block.push('\n');
+    block
+}
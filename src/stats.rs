@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::Page;
+
+/// Aggregate statistics of a sitemap, returned by [`Sitemap::stats`](crate::Sitemap::stats).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SitemapStats {
+    /// Total number of pages.
+    pub pages: usize,
+    /// Number of pages with `lastmod`.
+    pub with_lastmod: usize,
+    /// Number of pages without `lastmod`.
+    pub without_lastmod: usize,
+    /// Number of pages with a hash.
+    pub with_hash: usize,
+    /// Number of pages without a hash.
+    pub without_hash: usize,
+    /// Earliest `lastmod` of any page.
+    pub oldest_lastmod: Option<DateTime<FixedOffset>>,
+    /// Latest `lastmod` of any page.
+    pub newest_lastmod: Option<DateTime<FixedOffset>>,
+    /// Number of distinct hosts among the URLs.
+    pub hosts: usize,
+    /// Sum of the known response sizes in bytes.
+    pub total_bytes: usize,
+}
+
+impl SitemapStats {
+    /// Computes the statistics of `pages`.
+    pub(crate) fn of(pages: &[Page]) -> Self {
+        let lastmods = pages.iter().filter_map(|page| page.lastmod);
+        let with_lastmod = lastmods.clone().count();
+        let with_hash = pages.iter().filter(|page| page.md5_hash.is_some()).count();
+        let hosts: HashSet<_> = pages.iter().map(|page| page.url.host_str()).collect();
+
+        Self {
+            pages: pages.len(),
+            with_lastmod,
+            without_lastmod: pages.len() - with_lastmod,
+            with_hash,
+            without_hash: pages.len() - with_hash,
+            oldest_lastmod: lastmods.clone().min(),
+            newest_lastmod: lastmods.max(),
+            hosts: hosts.len(),
+            total_bytes: pages.iter().filter_map(|page| page.content_length).sum(),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use chrono::Datelike;
+use url::Url;
+
+use crate::Sitemap;
+
+/// Maximum number of URLs allowed in a single sitemap.
+pub const MAX_URLS: usize = 50_000;
+
+/// Maximum size of a single uncompressed sitemap in bytes.
+pub const MAX_BYTES: usize = 50 * 1024 * 1024;
+
+/// Maximum length of a URL in characters.
+pub const MAX_URL_LENGTH: usize = 2048;
+
+/// Violation of the sitemap protocol.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValidationError {
+    /// The sitemap contains more than [`MAX_URLS`] URLs.
+    TooManyUrls {
+        /// Number of URLs in the sitemap.
+        count: usize,
+    },
+    /// The URL is longer than [`MAX_URL_LENGTH`] characters.
+    UrlTooLong {
+        /// The offending URL.
+        url: Url,
+        /// Length of the URL in characters.
+        length: usize,
+    },
+    /// The `lastmod` cannot be written as a W3C datetime,
+    /// which requires a four-digit year.
+    InvalidLastmod {
+        /// URL of the page.
+        url: Url,
+    },
+    /// The `priority` is not a number between 0.0 and 1.0.
+    InvalidPriority {
+        /// URL of the page.
+        url: Url,
+        /// The offending priority.
+        priority: f32,
+    },
+    /// Pages are on more than one host, while search engines only accept URLs
+    /// on the host of the sitemap.
+    /// This is a warning returned by [`Sitemap::warnings`], as the sitemap is still valid.
+    MixedHosts {
+        /// Host of the first page, taken to be the host of the sitemap.
+        host: String,
+        /// URLs on other hosts.
+        urls: Vec<Url>,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyUrls { count } => {
+                write!(
+                    f,
+                    "sitemap has {} URLs, at most {} are allowed",
+                    count, MAX_URLS
+                )
+            }
+            Self::UrlTooLong { url, length } => {
+                write!(
+                    f,
+                    "URL {} has {} characters, at most {} are allowed",
+                    url, length, MAX_URL_LENGTH
+                )
+            }
+            Self::InvalidLastmod { url } => {
+                write!(f, "page {} has a lastmod outside years 0000-9999", url)
+            }
+            Self::InvalidPriority { url, priority } => {
+                write!(
+                    f,
+                    "page {} has priority {}, which is not between 0.0 and 1.0",
+                    url, priority
+                )
+            }
+            Self::MixedHosts { host, urls } => {
+                let urls = urls.iter().map(Url::as_str).collect::<Vec<_>>();
+                write!(
+                    f,
+                    "sitemap is for {}, but has URLs on other hosts: {}",
+                    host,
+                    urls.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Collects all protocol violations of the sitemap.
+pub(crate) fn validate(sitemap: &Sitemap) -> Vec<ValidationError> {
+    let mut errors = vec![];
+
+    if sitemap.pages.len() > MAX_URLS {
+        errors.push(ValidationError::TooManyUrls {
+            count: sitemap.pages.len(),
+        });
+    }
+
+    for page in &sitemap.pages {
+        let length = page.url.as_str().chars().count();
+        if length > MAX_URL_LENGTH {
+            errors.push(ValidationError::UrlTooLong {
+                url: page.url.clone(),
+                length,
+            });
+        }
+    }
+
+    errors
+}
+
+/// Collects findings that do not make the sitemap invalid, but are likely not what was intended.
+pub(crate) fn warnings(sitemap: &Sitemap) -> Vec<ValidationError> {
+    let mut warnings = vec![];
+
+    if let Some(host) = sitemap.pages.first().map(|page| page.url.host_str()) {
+        let urls = sitemap
+            .pages
+            .iter()
+            .filter(|page| page.url.host_str() != host)
+            .map(|page| page.url.clone())
+            .collect::<Vec<_>>();
+        if !urls.is_empty() {
+            warnings.push(ValidationError::MixedHosts {
+                host: host.unwrap_or_default().to_string(),
+                urls,
+            });
+        }
+    }
+
+    warnings
+}
+
+/// Collects all violations of the sitemap XML schema,
+/// including those found by [`validate`].
+pub(crate) fn validate_schema(sitemap: &Sitemap) -> Vec<ValidationError> {
+    let mut errors = validate(sitemap);
+
+    for page in &sitemap.pages {
+        if let Some(lastmod) = page.lastmod {
+            if !(0..=9999).contains(&lastmod.year()) {
+                errors.push(ValidationError::InvalidLastmod {
+                    url: page.url.clone(),
+                });
+            }
+        }
+        if let Some(priority) = page.priority {
+            if !(0.0..=1.0).contains(&priority) {
+                errors.push(ValidationError::InvalidPriority {
+                    url: page.url.clone(),
+                    priority,
+                });
+            }
+        }
+    }
+
+    errors
+}
@@ -1,21 +1,645 @@
-use chrono::{DateTime, Utc};
+// The yaserde derives define their impls inside anonymous constants.
+#![allow(non_local_definitions)]
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use chrono::{DateTime, FixedOffset, Utc};
 use url::Url;
 use yaserde_derive::{YaDeserialize, YaSerialize};
 
-use crate::{Page, Sitemap};
+use crate::{
+    Alternate, ChangeFreq, DeserializeOptions, HashStorage, ImageEntry, LastmodPrecision, Meta,
+    NewsEntry, Page, RssOptions, SerializeOptions, Sitemap, SitemapIndex, SitemapIndexEntry,
+    VideoEntry,
+};
+
+/// Name of the `<xhtml:meta>` element that stores the page `ETag`.
+const ETAG_META_NAME: &str = "auto_sitemap_etag";
+
+/// Namespaces of the sitemap extensions, declared on `<urlset>` only when pages use them;
+/// must match the attributes of [`SitemapSerde`].
+const EXTENSION_NAMESPACES: [(&str, &str); 3] = [
+    ("image", "http://www.google.com/schemas/sitemap-image/1.1"),
+    ("video", "http://www.google.com/schemas/sitemap-video/1.1"),
+    ("news", "http://www.google.com/schemas/sitemap-news/0.9"),
+];
+
+/// Returns the start of `<urlset>`,
+/// declaring the extension namespaces whose prefix `is_used` accepts.
+fn urlset_start(
+    is_used: impl Fn(&str) -> bool,
+) -> xml::writer::events::StartElementBuilder<'static> {
+    let mut start = xml::writer::XmlEvent::start_element("urlset")
+        .default_ns("http://www.sitemaps.org/schemas/sitemap/0.9")
+        .ns("xhtml", "http://www.w3.org/1999/xhtml");
+    for (prefix, uri) in EXTENSION_NAMESPACES {
+        if is_used(prefix) {
+            start = start.ns(prefix, uri);
+        }
+    }
+    start
+}
+
+/// Whether any of `pages` uses the extension with the namespace `prefix`.
+fn uses_namespace(pages: &[Page], prefix: &str) -> bool {
+    pages.iter().any(|page| match prefix {
+        "image" => !page.images.is_empty(),
+        "video" => !page.videos.is_empty(),
+        "news" => page.news.is_some(),
+        _ => false,
+    })
+}
+
+/// Configuration of the yaserde serializer.
+fn serializer_config(options: &SerializeOptions) -> yaserde::ser::Config {
+    yaserde::ser::Config {
+        perform_indent: options.indent,
+        write_document_declaration: options.xml_declaration,
+        indent_string: Some(options.indent_string.clone()),
+    }
+}
+
+/// Serializes a yaserde document, indenting it as configured.
+pub fn serialize<T: yaserde::YaSerialize, W: std::io::Write>(
+    document: &T,
+    writer: W,
+    options: &SerializeOptions,
+) -> Result<(), String> {
+    let mut serializer =
+        yaserde::ser::Serializer::new_from_writer(writer, &serializer_config(options));
+    write_prolog(&mut serializer, options)?;
+    document
+        .serialize(&mut serializer)
+        .map_err(|e| format!("failed to serialize: {}", e))?;
+
+    Ok(())
+}
+
+/// Writes the generator comment and the `<?xml-stylesheet?>` processing instruction
+/// if they are configured.
+fn write_prolog<W: std::io::Write>(
+    serializer: &mut yaserde::ser::Serializer<W>,
+    options: &SerializeOptions,
+) -> Result<(), String> {
+    if let Some(comment) = &options.generator_comment {
+        let now = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        // `--` may not appear inside a comment.
+        let text = format!(" {} at {} ", comment.replace("--", "- -"), now);
+        // Unlike processing instructions, comments do not make the writer emit the declaration first.
+        if options.xml_declaration {
+            serializer
+                .write(xml::writer::XmlEvent::StartDocument {
+                    version: xml::common::XmlVersion::Version10,
+                    encoding: Some("utf-8"),
+                    standalone: None,
+                })
+                .map_err(|e| format!("failed to serialize: {}", e))?;
+        }
+        serializer
+            .write(xml::writer::XmlEvent::comment(&text))
+            .map_err(|e| format!("failed to serialize: {}", e))?;
+    }
+
+    let Some(href) = &options.stylesheet else {
+        return Ok(());
+    };
+    let href = href.replace('&', "&amp;").replace('"', "&quot;");
+    let data = format!(r#"type="text/xsl" href="{}""#, href);
+    serializer
+        .write(xml::writer::XmlEvent::processing_instruction(
+            "xml-stylesheet",
+            Some(&data),
+        ))
+        .map_err(|e| format!("failed to serialize: {}", e))
+}
+
+/// Serializes pages one at a time, producing the same output as serializing a [`UrlsetSerde`]
+/// except that every extension namespace is declared, as the pages are not known in advance.
+/// Fails once there are more than `max_urls` pages.
+pub fn serialize_streaming<W: std::io::Write>(
+    pages: impl Iterator<Item = Page>,
+    writer: W,
+    options: &SerializeOptions,
+    max_urls: usize,
+) -> Result<(), String> {
+    let mut serializer =
+        yaserde::ser::Serializer::new_from_writer(writer, &serializer_config(options));
+    write_prolog(&mut serializer, options)?;
+
+    serializer
+        .write(urlset_start(|_| true))
+        .map_err(|e| format!("failed to serialize: {}", e))?;
+
+    for (i, page) in pages.enumerate() {
+        if i == max_urls {
+            return Err(format!("failed to serialize: more than {} URLs", max_urls));
+        }
+        serialize_page(&mut serializer, &page, options)
+            .map_err(|e| format!("failed to serialize: {}", e))?;
+    }
+
+    serializer
+        .write(xml::writer::XmlEvent::end_element())
+        .map_err(|e| format!("failed to serialize: {}", e))?;
+
+    Ok(())
+}
+
+/// Returns the hashes stored in `<!-- name: hash -->` comments, keyed by the `<loc>` of their `<url>`.
+pub fn hash_comments(bytes: &[u8], hash_meta_name: &str) -> Result<HashMap<Url, String>, String> {
+    let mut hashes = HashMap::new();
+    // Spares parsing the document again in the common case.
+    if !bytes.windows(4).any(|window| window == b"<!--") {
+        return Ok(hashes);
+    }
+
+    let mut depth = 0;
+    let mut in_loc = false;
+    let mut loc = String::new();
+    let mut hash = None;
+    let reader = xml::reader::EventReader::new_with_config(
+        bytes,
+        xml::reader::ParserConfig::new().ignore_comments(false),
+    );
+    for event in reader {
+        match event.map_err(|e| format!("failed to deserialize: {}", e))? {
+            xml::reader::XmlEvent::StartElement { name, .. } => {
+                depth += 1;
+                in_loc = depth == 3 && name.local_name == "loc";
+            }
+            xml::reader::XmlEvent::Characters(text) if in_loc => loc.push_str(&text),
+            xml::reader::XmlEvent::Comment(comment) if depth == 2 => {
+                hash = hash
+                    .or_else(|| parse_hash_comment(&comment, hash_meta_name).map(str::to_string));
+            }
+            xml::reader::XmlEvent::EndElement { .. } => {
+                in_loc = false;
+                depth -= 1;
+                if depth == 1 {
+                    let loc = std::mem::take(&mut loc);
+                    if let (Some(hash), Ok(url)) = (hash.take(), Url::parse(&loc)) {
+                        hashes.insert(url, hash);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(hashes)
+}
+
+/// Returns the hash stored in a `name: hash` comment, if it is one.
+fn parse_hash_comment<'a>(comment: &'a str, hash_meta_name: &str) -> Option<&'a str> {
+    comment
+        .trim()
+        .strip_prefix(hash_meta_name)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .map(str::trim)
+        .filter(|hash| hash.len() == 32)
+}
+
+/// Pages parsed one `<url>` element at a time.
+pub struct PageStream<R: std::io::Read> {
+    reader: xml::reader::EventReader<R>,
+    options: DeserializeOptions,
+    /// Start of the `<urlset>` element, which declares the namespaces.
+    root: Option<xml::reader::XmlEvent>,
+    depth: usize,
+    done: bool,
+}
+
+impl<R: std::io::Read> PageStream<R> {
+    pub fn new(reader: R, options: DeserializeOptions) -> Self {
+        let config = xml::reader::ParserConfig::new()
+            .trim_whitespace(true)
+            .whitespace_to_characters(true)
+            .cdata_to_characters(true)
+            .ignore_comments(false);
+        Self {
+            reader: xml::reader::EventReader::new_with_config(reader, config),
+            options,
+            root: None,
+            depth: 0,
+            done: false,
+        }
+    }
+
+    /// Copies the `<url>` element that has just started into a `<urlset>` of its own and parses it.
+    fn next_page(&mut self, start: xml::reader::XmlEvent) -> Result<Page, String> {
+        let mut buf = vec![];
+        let mut writer = xml::writer::EmitterConfig::new()
+            .write_document_declaration(false)
+            .create_writer(&mut buf);
+        if let Some(root) = self.root.as_ref().and_then(|root| root.as_writer_event()) {
+            writer
+                .write(root)
+                .map_err(|e| format!("failed to deserialize: {}", e))?;
+        }
+        let mut hash = None;
+        let mut depth = 0;
+        let mut event = start;
+        loop {
+            match &event {
+                xml::reader::XmlEvent::StartElement { .. } => depth += 1,
+                xml::reader::XmlEvent::EndElement { .. } => depth -= 1,
+                xml::reader::XmlEvent::Comment(comment) if depth == 1 && hash.is_none() => {
+                    hash = parse_hash_comment(comment, &self.options.hash_meta_name)
+                        .map(str::to_string);
+                }
+                _ => {}
+            }
+            if let Some(writer_event) = event.as_writer_event() {
+                writer
+                    .write(writer_event)
+                    .map_err(|e| format!("failed to deserialize: {}", e))?;
+            }
+            if depth == 0 {
+                break;
+            }
+            event = self
+                .reader
+                .next()
+                .map_err(|e| format!("failed to deserialize: {}", e))?;
+        }
+        self.depth -= 1;
+        writer
+            .write(xml::writer::XmlEvent::end_element())
+            .map_err(|e| format!("failed to deserialize: {}", e))?;
+
+        let sitemap_serde: SitemapSerde = yaserde::de::from_reader(&buf[..])
+            .map_err(|e| format!("failed to deserialize: {}", e))?;
+        let mut page = sitemap_serde
+            .into_sitemap(&self.options)?
+            .pages
+            .pop()
+            .ok_or_else(|| "failed to deserialize: page is missing".to_string())?;
+        if page.md5_hash.is_none() {
+            page.md5_hash = hash;
+        }
+        Ok(page)
+    }
+}
+
+impl<R: std::io::Read> Iterator for PageStream<R> {
+    type Item = Result<Page, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let event = match self.reader.next() {
+                Ok(event) => event,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(format!("failed to deserialize: {}", e)));
+                }
+            };
+            match &event {
+                xml::reader::XmlEvent::StartElement { name, .. } => {
+                    self.depth += 1;
+                    if self.depth == 1 {
+                        if name.local_name != "urlset" {
+                            self.done = true;
+                            return Some(Err(format!(
+                                "failed to deserialize: unexpected root element <{}>",
+                                name.local_name
+                            )));
+                        }
+                        self.root = Some(event);
+                        continue;
+                    }
+                    if self.depth == 2 && name.local_name == "url" {
+                        let page = self.next_page(event);
+                        self.done = page.is_err();
+                        return Some(page);
+                    }
+                }
+                xml::reader::XmlEvent::EndElement { .. } => self.depth -= 1,
+                xml::reader::XmlEvent::EndDocument => self.done = true,
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+/// Sizes in bytes of the parts of a document serialized with the default options,
+/// not counting the values in them.
+/// They are measured once by serializing sample pages, so that they follow the serializer.
+static PART_SIZES: LazyLock<PartSizes> = LazyLock::new(PartSizes::measure);
+
+/// See [`PART_SIZES`].
+struct PartSizes {
+    /// Document without pages.
+    empty: usize,
+    /// Document with pages but without their `<url>` elements or extension namespaces.
+    urlset: usize,
+    /// Declarations of [`EXTENSION_NAMESPACES`], in the same order.
+    namespaces: [usize; EXTENSION_NAMESPACES.len()],
+    url: usize,
+    /// `<lastmod>` in UTC, including the value, whose length is fixed.
+    lastmod: usize,
+    /// What an offset other than UTC adds to `<lastmod>`.
+    lastmod_offset: usize,
+    changefreq: usize,
+    priority: usize,
+    hash: usize,
+    etag: usize,
+    meta: usize,
+    alternate: usize,
+    hreflang: usize,
+    media: usize,
+    image: usize,
+    image_title: usize,
+    image_caption: usize,
+    video: usize,
+    video_thumbnail_loc: usize,
+    video_content_loc: usize,
+    video_player_loc: usize,
+    video_duration: usize,
+    /// `<news:news>`, including the publication date, whose length is fixed.
+    news: usize,
+}
 
+impl PartSizes {
+    fn measure() -> Self {
+        let options = SerializeOptions::default();
+        let size = |pages: &[Page]| {
+            let mut bytes = vec![];
+            serialize(&UrlsetSerde::new(pages, &options), &mut bytes, &options)
+                .expect("sample pages are serializable");
+            bytes.len()
+        };
+        let url = |path: &str| Url::parse(&format!("https://example.com/{}", path)).unwrap();
+        let page = Page::new(url(""));
+        let page_size = size(std::slice::from_ref(&page));
+        let url_size = size(&[page.clone(), page.clone()]) - page_size - page.url.as_str().len();
+        // Size that `change` adds to the page besides the `values` bytes of its values.
+        // The changed page is listed twice, so that the namespaces it uses are declared
+        // in both documents that are compared.
+        let part = |change: &dyn Fn(&mut Page), values: usize| {
+            let mut changed = page.clone();
+            change(&mut changed);
+            size(&[changed.clone(), changed.clone()]) - size(&[changed, page.clone()]) - values
+        };
+        // Size of the namespace declared for the extension that `change` adds.
+        let namespace = |change: &dyn Fn(&mut Page), values: usize| {
+            let mut changed = page.clone();
+            change(&mut changed);
+            size(&[changed]) - page_size - part(change, values) - values
+        };
+
+        let utc = DateTime::from_timestamp(0, 0).unwrap();
+        let offset = FixedOffset::east_opt(2 * 60 * 60).unwrap();
+        let lastmod = part(&|page| page.lastmod = Some(utc.fixed_offset()), 0);
+        let loc = url("loc");
+        let loc_len = loc.as_str().len();
+        let alternate = Alternate {
+            href: loc.clone(),
+            hreflang: None,
+            media: None,
+        };
+        let alternate_size = part(&|page| page.alternates.push(alternate.clone()), loc_len);
+        let image = ImageEntry {
+            loc: loc.clone(),
+            title: None,
+            caption: None,
+        };
+        let add_image = |page: &mut Page| page.images.push(image.clone());
+        let image_size = part(&add_image, loc_len);
+        let video = VideoEntry {
+            thumbnail_loc: None,
+            title: "t".to_string(),
+            description: "d".to_string(),
+            content_loc: None,
+            player_loc: None,
+            duration: None,
+        };
+        let add_video = |page: &mut Page| page.videos.push(video.clone());
+        let video_size = part(&add_video, 2);
+        let video_part = |video: VideoEntry, values: usize| {
+            part(&|page| page.videos.push(video.clone()), 2 + values) - video_size
+        };
+        let news = NewsEntry {
+            publication_name: "n".to_string(),
+            publication_language: "l".to_string(),
+            publication_date: utc,
+            title: "t".to_string(),
+        };
+        let add_news = |page: &mut Page| page.news = Some(news.clone());
+
+        Self {
+            empty: size(&[]),
+            urlset: page_size - url_size - page.url.as_str().len(),
+            namespaces: [
+                namespace(&add_image, loc_len),
+                namespace(&add_video, 2),
+                namespace(&add_news, 3),
+            ],
+            url: url_size,
+            lastmod,
+            lastmod_offset: part(&|page| page.lastmod = Some(utc.with_timezone(&offset)), 0)
+                - lastmod,
+            changefreq: part(
+                &|page| page.changefreq = Some(ChangeFreq::Weekly),
+                ChangeFreq::Weekly.as_str().len(),
+            ),
+            priority: part(&|page| page.priority = Some(0.5), 3),
+            hash: part(&|page| page.md5_hash = Some("0".repeat(32)), 32),
+            etag: part(&|page| page.etag = Some("e".to_string()), 1),
+            meta: part(
+                &|page| {
+                    page.extra_meta.push(Meta {
+                        name: "n".to_string(),
+                        content: "c".to_string(),
+                    })
+                },
+                2,
+            ),
+            alternate: alternate_size,
+            hreflang: part(
+                &|page| {
+                    page.alternates.push(Alternate {
+                        hreflang: Some("l".to_string()),
+                        ..alternate.clone()
+                    })
+                },
+                loc_len + 1,
+            ) - alternate_size,
+            media: part(
+                &|page| {
+                    page.alternates.push(Alternate {
+                        media: Some("m".to_string()),
+                        ..alternate.clone()
+                    })
+                },
+                loc_len + 1,
+            ) - alternate_size,
+            image: image_size,
+            image_title: part(
+                &|page| {
+                    page.images.push(ImageEntry {
+                        title: Some("t".to_string()),
+                        ..image.clone()
+                    })
+                },
+                loc_len + 1,
+            ) - image_size,
+            image_caption: part(
+                &|page| {
+                    page.images.push(ImageEntry {
+                        caption: Some("c".to_string()),
+                        ..image.clone()
+                    })
+                },
+                loc_len + 1,
+            ) - image_size,
+            video: video_size,
+            video_thumbnail_loc: video_part(
+                VideoEntry {
+                    thumbnail_loc: Some(loc.clone()),
+                    ..video.clone()
+                },
+                loc_len,
+            ),
+            video_content_loc: video_part(
+                VideoEntry {
+                    content_loc: Some(loc.clone()),
+                    ..video.clone()
+                },
+                loc_len,
+            ),
+            video_player_loc: video_part(
+                VideoEntry {
+                    player_loc: Some(loc.clone()),
+                    ..video.clone()
+                },
+                loc_len,
+            ),
+            video_duration: video_part(
+                VideoEntry {
+                    duration: Some(1),
+                    ..video.clone()
+                },
+                1,
+            ),
+            news: part(&add_news, 3),
+        }
+    }
+}
+
+/// Estimates the size in bytes of the document that serializing `pages` with the default
+/// options gives, from the lengths of the values.
+/// Escaping of special characters is not accounted for.
+pub fn approx_size(pages: &[Page]) -> usize {
+    let sizes = &*PART_SIZES;
+    if pages.is_empty() {
+        return sizes.empty;
+    }
+
+    let mut size = sizes.urlset;
+    for ((prefix, _), namespace) in EXTENSION_NAMESPACES.iter().zip(sizes.namespaces) {
+        if uses_namespace(pages, prefix) {
+            size += namespace;
+        }
+    }
+    for page in pages {
+        size += sizes.url + page.url.as_str().len();
+        if let Some(lastmod) = page.lastmod {
+            size += sizes.lastmod;
+            if lastmod.offset().local_minus_utc() != 0 {
+                size += sizes.lastmod_offset;
+            }
+        }
+        size += page
+            .changefreq
+            .map_or(0, |changefreq| sizes.changefreq + changefreq.as_str().len());
+        size += page
+            .priority
+            .map_or(0, |priority| sizes.priority + priority.to_string().len());
+        size += page
+            .md5_hash
+            .as_ref()
+            .map_or(0, |hash| sizes.hash + hash.len());
+        size += page.etag.as_ref().map_or(0, |etag| sizes.etag + etag.len());
+        for meta in &page.extra_meta {
+            size += sizes.meta + meta.name.len() + meta.content.len();
+        }
+        for alternate in &page.alternates {
+            size += sizes.alternate + alternate.href.as_str().len();
+            size += alternate
+                .hreflang
+                .as_ref()
+                .map_or(0, |hreflang| sizes.hreflang + hreflang.len());
+            size += alternate
+                .media
+                .as_ref()
+                .map_or(0, |media| sizes.media + media.len());
+        }
+        for image in &page.images {
+            size += sizes.image + image.loc.as_str().len();
+            size += image
+                .title
+                .as_ref()
+                .map_or(0, |title| sizes.image_title + title.len());
+            size += image
+                .caption
+                .as_ref()
+                .map_or(0, |caption| sizes.image_caption + caption.len());
+        }
+        for video in &page.videos {
+            let url = |part: usize, url: &Option<Url>| {
+                url.as_ref().map_or(0, |url| part + url.as_str().len())
+            };
+            size += sizes.video
+                + video.title.len()
+                + video.description.len()
+                + url(sizes.video_thumbnail_loc, &video.thumbnail_loc)
+                + url(sizes.video_content_loc, &video.content_loc)
+                + url(sizes.video_player_loc, &video.player_loc);
+            size += video.duration.map_or(0, |duration| {
+                sizes.video_duration + duration.to_string().len()
+            });
+        }
+        if let Some(news) = &page.news {
+            size += sizes.news
+                + news.publication_name.len()
+                + news.publication_language.len()
+                + news.title.len();
+        }
+    }
+
+    size
+}
+
+/// Returns the local name of the root element, e.g. `urlset`.
+pub fn root_element_name(bytes: &[u8]) -> Result<String, String> {
+    for event in xml::reader::EventReader::new(bytes) {
+        match event.map_err(|e| format!("failed to deserialize: {}", e))? {
+            xml::reader::XmlEvent::StartElement { name, .. } => return Ok(name.local_name),
+            xml::reader::XmlEvent::EndDocument => break,
+            _ => {}
+        }
+    }
+
+    Err("failed to deserialize: document has no root element".to_string())
+}
+
+/// Datetime written with the given precision.
+/// The flag tells whether the offset was missing when deserializing, so UTC was assumed.
 #[derive(Debug, PartialEq)]
-pub struct DateTimeSerde<Tz: chrono::TimeZone>(pub DateTime<Tz>);
+pub struct DateTimeSerde<Tz: chrono::TimeZone>(pub DateTime<Tz>, pub LastmodPrecision, pub bool);
 
-impl From<DateTimeSerde<Utc>> for DateTime<Utc> {
-    fn from(val: DateTimeSerde<Utc>) -> Self {
+impl<Tz: chrono::TimeZone> From<DateTimeSerde<Tz>> for DateTime<Tz> {
+    fn from(val: DateTimeSerde<Tz>) -> Self {
         val.0
     }
 }
 
-impl From<DateTime<Utc>> for DateTimeSerde<Utc> {
-    fn from(val: DateTime<Utc>) -> Self {
-        DateTimeSerde(val)
+impl<Tz: chrono::TimeZone> From<DateTime<Tz>> for DateTimeSerde<Tz> {
+    fn from(val: DateTime<Tz>) -> Self {
+        DateTimeSerde(val, LastmodPrecision::default(), false)
     }
 }
 
@@ -28,101 +652,547 @@ impl From<UrlSerde> for Url {
     }
 }
 
-#[derive(Debug, PartialEq, YaSerialize, YaDeserialize)]
+/// Text content of an element in the default namespace, e.g. `<changefreq>`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TextSerde(pub String);
+
+/// `<urlset>` as deserialized; it is serialized through [`UrlsetSerde`].
+#[derive(Debug, PartialEq, YaDeserialize)]
 #[yaserde(
     rename = "urlset",
     namespace = "http://www.sitemaps.org/schemas/sitemap/0.9"
     namespace = "xhtml: http://www.w3.org/1999/xhtml"
+    namespace = "image: http://www.google.com/schemas/sitemap-image/1.1"
+    namespace = "video: http://www.google.com/schemas/sitemap-video/1.1"
+    namespace = "news: http://www.google.com/schemas/sitemap-news/0.9"
 )]
 pub struct SitemapSerde {
     #[yaserde(rename = "url")]
     pub pages: Vec<PageSerde>,
 }
 
+/// Pages serialized as `<urlset>`, written by hand so that extension namespaces are only declared
+/// when they are used and hashes can be stored in comments.
+pub struct UrlsetSerde<'a> {
+    pages: &'a [Page],
+    options: &'a SerializeOptions,
+}
+
+impl<'a> UrlsetSerde<'a> {
+    pub fn new(pages: &'a [Page], options: &'a SerializeOptions) -> Self {
+        Self { pages, options }
+    }
+}
+
+impl yaserde::YaSerialize for UrlsetSerde<'_> {
+    fn serialize<W>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String>
+    where
+        W: std::io::Write,
+    {
+        let start = urlset_start(|prefix| uses_namespace(self.pages, prefix));
+        writer.write(start).map_err(|e| e.to_string())?;
+
+        for page in self.pages {
+            serialize_page(writer, page, self.options)?;
+        }
+
+        writer
+            .write(xml::writer::XmlEvent::end_element())
+            .map_err(|e| e.to_string())
+    }
+
+    fn serialize_attributes(
+        &self,
+        source_attributes: Vec<xml::attribute::OwnedAttribute>,
+        source_namespace: xml::namespace::Namespace,
+    ) -> Result<
+        (
+            Vec<xml::attribute::OwnedAttribute>,
+            xml::namespace::Namespace,
+        ),
+        String,
+    > {
+        Ok((source_attributes, source_namespace))
+    }
+}
+
+/// Writes `page` as `<url>`, with its hash in a comment if configured so.
+fn serialize_page<W: std::io::Write>(
+    writer: &mut yaserde::ser::Serializer<W>,
+    page: &Page,
+    options: &SerializeOptions,
+) -> Result<(), String> {
+    let page_serde = PageSerde::new(page, options);
+    let hash = match (&page.md5_hash, options.hash_storage) {
+        (Some(hash), HashStorage::Comment) => hash,
+        _ => {
+            writer.set_start_event_name(Some("url".to_string()));
+            writer.set_skip_start_end(false);
+            return yaserde::YaSerialize::serialize(&page_serde, writer);
+        }
+    };
+
+    writer
+        .write(xml::writer::XmlEvent::start_element("url"))
+        .map_err(|e| e.to_string())?;
+    // `--` may not appear inside a comment.
+    let comment = format!(
+        " {}: {} ",
+        options.hash_meta_name.replace("--", "- -"),
+        hash
+    );
+    writer
+        .write(xml::writer::XmlEvent::comment(&comment))
+        .map_err(|e| e.to_string())?;
+    writer.set_skip_start_end(true);
+    yaserde::YaSerialize::serialize(&page_serde, writer)?;
+    writer
+        .write(xml::writer::XmlEvent::end_element())
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, PartialEq, YaSerialize, YaDeserialize)]
+#[yaserde(
+    rename = "sitemapindex",
+    namespace = "http://www.sitemaps.org/schemas/sitemap/0.9"
+)]
+pub struct SitemapIndexSerde {
+    #[yaserde(rename = "sitemap")]
+    pub sitemaps: Vec<SitemapIndexEntrySerde>,
+}
+
+#[derive(Debug, PartialEq, YaSerialize)]
+#[yaserde(rename = "rss")]
+pub struct RssSerde {
+    #[yaserde(attribute)]
+    pub version: String,
+    pub channel: RssChannelSerde,
+}
+
+#[derive(Debug, PartialEq, YaSerialize)]
+pub struct RssChannelSerde {
+    pub title: String,
+    pub link: String,
+    pub description: String,
+    #[yaserde(rename = "item")]
+    pub items: Vec<RssItemSerde>,
+}
+
+#[derive(Debug, PartialEq, YaSerialize)]
+pub struct RssItemSerde {
+    pub title: String,
+    pub link: String,
+    pub guid: String,
+    #[yaserde(rename = "pubDate")]
+    pub pub_date: String,
+}
+
+impl RssSerde {
+    /// Lists the most recently modified pages, leaving out those without `lastmod`.
+    pub fn new(pages: &[Page], options: &RssOptions) -> Self {
+        let mut pages: Vec<_> = pages
+            .iter()
+            .filter_map(|page| Some((page, page.lastmod?)))
+            .collect();
+        pages.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        let items = pages
+            .into_iter()
+            .take(options.max_items)
+            .map(|(page, lastmod)| RssItemSerde {
+                title: page.url.to_string(),
+                link: page.url.to_string(),
+                guid: page.url.to_string(),
+                pub_date: lastmod.to_rfc2822(),
+            })
+            .collect();
+
+        Self {
+            version: "2.0".to_string(),
+            channel: RssChannelSerde {
+                title: options.title.clone(),
+                link: options.link.to_string(),
+                description: options.description.clone(),
+                items,
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, YaSerialize, YaDeserialize)]
+pub struct SitemapIndexEntrySerde {
+    pub loc: Option<UrlSerde>,
+    pub lastmod: Option<DateTimeSerde<FixedOffset>>,
+}
+
 #[derive(Debug, PartialEq, YaSerialize, YaDeserialize)]
 pub struct PageSerde {
     #[yaserde(rename = "loc")]
     pub url: Option<UrlSerde>,
-    pub lastmod: Option<DateTimeSerde<Utc>>,
+    pub lastmod: Option<DateTimeSerde<FixedOffset>>,
+    pub changefreq: Option<TextSerde>,
+    pub priority: Option<TextSerde>,
+    #[yaserde(rename = "link", prefix = "xhtml")]
+    pub links: Vec<LinkSerde>,
     #[yaserde(prefix = "xhtml")]
-    pub meta: Vec<Meta>,
+    pub meta: Vec<MetaSerde>,
+    #[yaserde(rename = "image", prefix = "image")]
+    pub images: Vec<ImageSerde>,
+    #[yaserde(rename = "video", prefix = "video")]
+    pub videos: Vec<VideoSerde>,
+    #[yaserde(prefix = "news")]
+    pub news: Option<NewsSerde>,
+}
+
+#[derive(Debug, PartialEq, YaSerialize, YaDeserialize)]
+#[yaserde(namespace = "image: http://www.google.com/schemas/sitemap-image/1.1")]
+pub struct ImageSerde {
+    #[yaserde(prefix = "image")]
+    pub loc: Option<UrlSerde>,
+    #[yaserde(prefix = "image")]
+    pub title: Option<String>,
+    #[yaserde(prefix = "image")]
+    pub caption: Option<String>,
 }
 
-impl From<&Sitemap> for SitemapSerde {
-    fn from(sitemap: &Sitemap) -> Self {
-        let pages = sitemap
+#[derive(Debug, PartialEq, YaSerialize, YaDeserialize)]
+#[yaserde(namespace = "video: http://www.google.com/schemas/sitemap-video/1.1")]
+pub struct VideoSerde {
+    #[yaserde(prefix = "video")]
+    pub thumbnail_loc: Option<UrlSerde>,
+    #[yaserde(prefix = "video")]
+    pub title: Option<String>,
+    #[yaserde(prefix = "video")]
+    pub description: Option<String>,
+    #[yaserde(prefix = "video")]
+    pub content_loc: Option<UrlSerde>,
+    #[yaserde(prefix = "video")]
+    pub player_loc: Option<UrlSerde>,
+    #[yaserde(prefix = "video")]
+    pub duration: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, YaSerialize, YaDeserialize)]
+#[yaserde(namespace = "news: http://www.google.com/schemas/sitemap-news/0.9")]
+pub struct NewsSerde {
+    #[yaserde(prefix = "news")]
+    pub publication: Option<PublicationSerde>,
+    #[yaserde(prefix = "news")]
+    pub publication_date: Option<DateTimeSerde<Utc>>,
+    #[yaserde(prefix = "news")]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, PartialEq, YaSerialize, YaDeserialize)]
+#[yaserde(namespace = "news: http://www.google.com/schemas/sitemap-news/0.9")]
+pub struct PublicationSerde {
+    #[yaserde(prefix = "news")]
+    pub name: Option<String>,
+    #[yaserde(prefix = "news")]
+    pub language: Option<String>,
+}
+
+impl SitemapSerde {
+    pub fn into_sitemap(self, options: &DeserializeOptions) -> Result<Sitemap, String> {
+        let pages = self
             .pages
+            .into_iter()
+            .map(|page| page.into_page(options))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Sitemap { pages })
+    }
+}
+
+impl From<&SitemapIndex> for SitemapIndexSerde {
+    fn from(index: &SitemapIndex) -> Self {
+        let sitemaps = index
+            .sitemaps
             .iter()
-            .map(|page| page.into())
-            .collect::<Vec<_>>();
-        SitemapSerde { pages }
+            .map(|entry| SitemapIndexEntrySerde {
+                loc: Some(UrlSerde(entry.loc.clone())),
+                lastmod: entry.lastmod.map(|lastmod| lastmod.into()),
+            })
+            .collect();
+        Self { sitemaps }
     }
 }
 
-impl TryFrom<SitemapSerde> for Sitemap {
+impl TryFrom<SitemapIndexSerde> for SitemapIndex {
     type Error = String;
-    fn try_from(sitemap_serde: SitemapSerde) -> Result<Self, Self::Error> {
-        let pages = sitemap_serde
-            .pages
+    fn try_from(index_serde: SitemapIndexSerde) -> Result<Self, Self::Error> {
+        let sitemaps = index_serde
+            .sitemaps
             .into_iter()
-            .map(|page| page.try_into())
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(Self { pages })
+            .map(|entry| {
+                Ok(SitemapIndexEntry {
+                    loc: entry
+                        .loc
+                        .ok_or_else(|| "sitemap URL is missing".to_string())?
+                        .into(),
+                    lastmod: entry.lastmod.map(|lastmod| lastmod.into()),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { sitemaps })
     }
 }
 
-impl From<&Page> for PageSerde {
-    fn from(page: &Page) -> Self {
-        let meta = page.md5_hash.as_ref().map(|hash| Meta {
-            name: "auto_sitemap_md5_hash".to_string(),
-            content: hash.clone(),
+impl PageSerde {
+    fn new(page: &Page, options: &SerializeOptions) -> Self {
+        // Otherwise, the hash is written as a comment by `serialize_page`.
+        let hash = page
+            .md5_hash
+            .as_ref()
+            .filter(|_| options.hash_storage == HashStorage::Meta)
+            .map(|hash| MetaSerde {
+                name: options.hash_meta_name.clone(),
+                content: hash.clone(),
+            });
+        let etag = page.etag.as_ref().map(|etag| MetaSerde {
+            name: ETAG_META_NAME.to_string(),
+            content: etag.clone(),
+        });
+        let extra_meta = page.extra_meta.iter().map(|meta| MetaSerde {
+            name: meta.name.clone(),
+            content: meta.content.clone(),
         });
         Self {
             url: Some(UrlSerde(page.url.clone())),
-            lastmod: page.lastmod.map(|lastmod| lastmod.into()),
-            meta: meta.into_iter().collect(),
+            lastmod: page
+                .lastmod
+                .filter(|lastmod| options.min_lastmod.is_none_or(|min| *lastmod >= min))
+                .map(|lastmod| DateTimeSerde(lastmod, options.lastmod_precision, false)),
+            changefreq: page
+                .changefreq
+                .map(|changefreq| TextSerde(changefreq.as_str().to_string())),
+            priority: page
+                .priority
+                .map(|priority| TextSerde(priority.to_string())),
+            links: page
+                .alternates
+                .iter()
+                .map(|alternate| alternate.into())
+                .collect(),
+            meta: hash.into_iter().chain(etag).chain(extra_meta).collect(),
+            images: page.images.iter().map(|image| image.into()).collect(),
+            videos: page.videos.iter().map(|video| video.into()).collect(),
+            news: page.news.as_ref().map(|news| news.into()),
         }
     }
-}
 
-impl TryFrom<PageSerde> for Page {
-    type Error = String;
-
-    fn try_from(page_serde: PageSerde) -> Result<Self, Self::Error> {
-        let hash = page_serde.meta.into_iter().find_map(|meta| {
+    fn into_page(self, options: &DeserializeOptions) -> Result<Page, String> {
+        let mut hash = None;
+        let mut etag = None;
+        let mut extra_meta = vec![];
+        for meta in self.meta {
             let name = meta.name.trim();
             let content = meta.content.trim();
-            if name == "auto_sitemap_md5_hash" && content.len() == 32 {
-                Some(content.to_string())
+            if hash.is_none() && name == options.hash_meta_name && content.len() == 32 {
+                hash = Some(content.to_string());
+            } else if etag.is_none() && name == ETAG_META_NAME {
+                etag = Some(meta.content);
             } else {
-                None
+                extra_meta.push(Meta {
+                    name: meta.name,
+                    content: meta.content,
+                });
             }
-        });
-        Ok(Self {
-            url: page_serde
+        }
+        Ok(Page {
+            url: self
                 .url
                 .ok_or_else(|| "page URL is missing".to_string())?
                 .into(),
-            lastmod: page_serde.lastmod.map(|lastmod| lastmod.into()),
+            lastmod: self.lastmod.map(|DateTimeSerde(lastmod, _, naive)| {
+                if naive {
+                    lastmod
+                        .naive_utc()
+                        .and_local_timezone(options.naive_offset)
+                        .single()
+                        .unwrap_or(lastmod)
+                } else {
+                    lastmod
+                }
+            }),
+            changefreq: self
+                .changefreq
+                .map(|changefreq| changefreq.0.trim().parse())
+                .transpose()?,
+            priority: self
+                .priority
+                .map(|priority| {
+                    priority
+                        .0
+                        .trim()
+                        .parse()
+                        .map_err(|e| format!("invalid priority {}: {}", priority.0, e))
+                })
+                .transpose()?,
             md5_hash: hash,
+            etag,
+            content_length: None,
+            images: self
+                .images
+                .into_iter()
+                .map(|image| image.try_into())
+                .collect::<Result<Vec<_>, _>>()?,
+            videos: self
+                .videos
+                .into_iter()
+                .map(|video| video.try_into())
+                .collect::<Result<Vec<_>, _>>()?,
+            news: self.news.map(|news| news.try_into()).transpose()?,
+            alternates: self
+                .links
+                .into_iter()
+                .filter(|link| link.rel.trim().eq_ignore_ascii_case("alternate"))
+                .map(|link| link.try_into())
+                .collect::<Result<Vec<_>, _>>()?,
+            extra_meta,
         })
     }
 }
 
-impl yaserde::YaSerialize for DateTimeSerde<Utc> {
+impl From<&ImageEntry> for ImageSerde {
+    fn from(image: &ImageEntry) -> Self {
+        Self {
+            loc: Some(UrlSerde(image.loc.clone())),
+            title: image.title.clone(),
+            caption: image.caption.clone(),
+        }
+    }
+}
+
+impl TryFrom<ImageSerde> for ImageEntry {
+    type Error = String;
+
+    fn try_from(image_serde: ImageSerde) -> Result<Self, Self::Error> {
+        Ok(Self {
+            loc: image_serde
+                .loc
+                .ok_or_else(|| "image URL is missing".to_string())?
+                .into(),
+            title: image_serde.title,
+            caption: image_serde.caption,
+        })
+    }
+}
+
+impl From<&Alternate> for LinkSerde {
+    fn from(alternate: &Alternate) -> Self {
+        Self {
+            rel: "alternate".to_string(),
+            hreflang: alternate.hreflang.clone(),
+            media: alternate.media.clone(),
+            href: alternate.href.to_string(),
+        }
+    }
+}
+
+impl TryFrom<LinkSerde> for Alternate {
+    type Error = String;
+
+    fn try_from(link_serde: LinkSerde) -> Result<Self, Self::Error> {
+        Ok(Self {
+            href: Url::parse(link_serde.href.trim())
+                .map_err(|e| format!("invalid alternate URL {}: {}", link_serde.href, e))?,
+            hreflang: link_serde.hreflang,
+            media: link_serde.media,
+        })
+    }
+}
+
+impl From<&VideoEntry> for VideoSerde {
+    fn from(video: &VideoEntry) -> Self {
+        Self {
+            thumbnail_loc: video.thumbnail_loc.clone().map(UrlSerde),
+            title: Some(video.title.clone()),
+            description: Some(video.description.clone()),
+            content_loc: video.content_loc.clone().map(UrlSerde),
+            player_loc: video.player_loc.clone().map(UrlSerde),
+            duration: video.duration,
+        }
+    }
+}
+
+impl TryFrom<VideoSerde> for VideoEntry {
+    type Error = String;
+
+    fn try_from(video_serde: VideoSerde) -> Result<Self, Self::Error> {
+        Ok(Self {
+            thumbnail_loc: video_serde.thumbnail_loc.map(|loc| loc.into()),
+            title: video_serde
+                .title
+                .ok_or_else(|| "video title is missing".to_string())?,
+            description: video_serde
+                .description
+                .ok_or_else(|| "video description is missing".to_string())?,
+            content_loc: video_serde.content_loc.map(|loc| loc.into()),
+            player_loc: video_serde.player_loc.map(|loc| loc.into()),
+            duration: video_serde.duration,
+        })
+    }
+}
+
+impl From<&NewsEntry> for NewsSerde {
+    fn from(news: &NewsEntry) -> Self {
+        Self {
+            publication: Some(PublicationSerde {
+                name: Some(news.publication_name.clone()),
+                language: Some(news.publication_language.clone()),
+            }),
+            publication_date: Some(news.publication_date.into()),
+            title: Some(news.title.clone()),
+        }
+    }
+}
+
+impl TryFrom<NewsSerde> for NewsEntry {
+    type Error = String;
+
+    fn try_from(news_serde: NewsSerde) -> Result<Self, Self::Error> {
+        let publication = news_serde
+            .publication
+            .ok_or_else(|| "news publication is missing".to_string())?;
+        Ok(Self {
+            publication_name: publication
+                .name
+                .ok_or_else(|| "news publication name is missing".to_string())?,
+            publication_language: publication
+                .language
+                .ok_or_else(|| "news publication language is missing".to_string())?,
+            publication_date: news_serde
+                .publication_date
+                .ok_or_else(|| "news publication date is missing".to_string())?
+                .into(),
+            title: news_serde
+                .title
+                .ok_or_else(|| "news title is missing".to_string())?,
+        })
+    }
+}
+
+impl<Tz: chrono::TimeZone> yaserde::YaSerialize for DateTimeSerde<Tz>
+where
+    Tz::Offset: std::fmt::Display,
+{
     fn serialize<W>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String>
     where
         W: std::io::Write,
     {
+        let name = writer
+            .get_start_event_name()
+            .unwrap_or_else(|| "lastmod".to_string());
         writer
-            // TODO: make this more robust because this only works if `DateTime` is used as a value
-            // of `lastmod` element.
-            .write(xml::writer::XmlEvent::start_element("lastmod"))
+            .write(xml::writer::XmlEvent::start_element(name.as_str()))
             .map_err(|e| e.to_string())?;
+        let text = match self.1 {
+            LastmodPrecision::DateOnly => self.0.format("%Y-%m-%d").to_string(),
+            LastmodPrecision::Seconds => self.0.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            LastmodPrecision::Millis => self.0.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        };
         writer
-            .write(xml::writer::XmlEvent::characters(
-                &self.0.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-            ))
+            .write(xml::writer::XmlEvent::characters(&text))
             .map_err(|e| e.to_string())?;
         writer
             .write(xml::writer::XmlEvent::end_element())
@@ -145,7 +1215,10 @@ impl yaserde::YaSerialize for DateTimeSerde<Utc> {
     }
 }
 
-impl yaserde::YaDeserialize for DateTimeSerde<Utc> {
+impl<Tz: chrono::TimeZone> yaserde::YaDeserialize for DateTimeSerde<Tz>
+where
+    DateTime<Tz>: From<DateTime<FixedOffset>>,
+{
     fn deserialize<R: std::io::Read>(
         reader: &mut yaserde::de::Deserializer<R>,
     ) -> Result<Self, String> {
@@ -153,9 +1226,9 @@ impl yaserde::YaDeserialize for DateTimeSerde<Utc> {
             match reader.next_event()? {
                 xml::reader::XmlEvent::StartElement { .. } => {}
                 xml::reader::XmlEvent::Characters(ref text_content) => {
-                    return DateTime::parse_from_rfc3339(text_content)
-                        .map_err(|e| format!("failed to deserialize `{text_content}`: {e}"))
-                        .map(|dt| DateTimeSerde(dt.with_timezone(&Utc)));
+                    return parse_w3c_datetime(text_content).map(|(dt, naive)| {
+                        DateTimeSerde(dt.into(), LastmodPrecision::default(), naive)
+                    });
                 }
                 _ => {
                     break;
@@ -166,15 +1239,35 @@ impl yaserde::YaDeserialize for DateTimeSerde<Utc> {
     }
 }
 
+/// Parses a W3C datetime, which may omit the time (midnight UTC is assumed then)
+/// or the offset (UTC is assumed then, which is indicated by the returned flag).
+fn parse_w3c_datetime(text: &str) -> Result<(DateTime<FixedOffset>, bool), String> {
+    let text = text.trim();
+    DateTime::parse_from_rfc3339(text)
+        .map(|datetime| (datetime, false))
+        .or_else(|e| {
+            chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f")
+                .map(|datetime| (datetime, true))
+                .or_else(|_| {
+                    chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                        .map(|date| (date.and_time(chrono::NaiveTime::MIN), false))
+                })
+                .map(|(datetime, naive)| (datetime.and_utc().fixed_offset(), naive))
+                .map_err(|_| e)
+        })
+        .map_err(|e| format!("failed to deserialize `{text}`: {e}"))
+}
+
 impl yaserde::YaSerialize for UrlSerde {
     fn serialize<W>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String>
     where
         W: std::io::Write,
     {
+        let name = writer
+            .get_start_event_name()
+            .unwrap_or_else(|| "loc".to_string());
         writer
-            // TODO: make this more robust because this only works if `Url` is used as a value
-            // of `loc` element.
-            .write(xml::writer::XmlEvent::start_element("loc"))
+            .write(xml::writer::XmlEvent::start_element(name.as_str()))
             .map_err(|e| e.to_string())?;
         writer
             .write(xml::writer::XmlEvent::characters(self.0.as_str()))
@@ -200,6 +1293,60 @@ impl yaserde::YaSerialize for UrlSerde {
     }
 }
 
+impl yaserde::YaSerialize for TextSerde {
+    fn serialize<W>(&self, writer: &mut yaserde::ser::Serializer<W>) -> Result<(), String>
+    where
+        W: std::io::Write,
+    {
+        let name = writer
+            .get_start_event_name()
+            .ok_or_else(|| "element name is missing".to_string())?;
+        writer
+            .write(xml::writer::XmlEvent::start_element(name.as_str()))
+            .map_err(|e| e.to_string())?;
+        writer
+            .write(xml::writer::XmlEvent::characters(&self.0))
+            .map_err(|e| e.to_string())?;
+        writer
+            .write(xml::writer::XmlEvent::end_element())
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn serialize_attributes(
+        &self,
+        source_attributes: Vec<xml::attribute::OwnedAttribute>,
+        source_namespace: xml::namespace::Namespace,
+    ) -> Result<
+        (
+            Vec<xml::attribute::OwnedAttribute>,
+            xml::namespace::Namespace,
+        ),
+        String,
+    > {
+        Ok((source_attributes, source_namespace))
+    }
+}
+
+impl yaserde::YaDeserialize for TextSerde {
+    fn deserialize<R: std::io::Read>(
+        reader: &mut yaserde::de::Deserializer<R>,
+    ) -> Result<Self, String> {
+        loop {
+            match reader.next_event()? {
+                xml::reader::XmlEvent::StartElement { .. } => {}
+                xml::reader::XmlEvent::Characters(text_content) => {
+                    return Ok(TextSerde(text_content));
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+        Err("Unable to parse".to_string())
+    }
+}
+
 impl yaserde::YaDeserialize for UrlSerde {
     fn deserialize<R: std::io::Read>(
         reader: &mut yaserde::de::Deserializer<R>,
@@ -223,7 +1370,20 @@ impl yaserde::YaDeserialize for UrlSerde {
 
 #[derive(Debug, PartialEq, Clone, YaSerialize, YaDeserialize)]
 #[yaserde(namespace = "xhtml: http://www.w3.org/1999/xhtml")]
-pub struct Meta {
+pub struct LinkSerde {
+    #[yaserde(attribute)]
+    rel: String,
+    #[yaserde(attribute)]
+    hreflang: Option<String>,
+    #[yaserde(attribute)]
+    media: Option<String>,
+    #[yaserde(attribute)]
+    href: String,
+}
+
+#[derive(Debug, PartialEq, Clone, YaSerialize, YaDeserialize)]
+#[yaserde(namespace = "xhtml: http://www.w3.org/1999/xhtml")]
+pub struct MetaSerde {
     #[yaserde(attribute)]
     name: String,
     #[yaserde(attribute)]
@@ -1,5 +1,5 @@
 use auto_sitemap::*;
-use chrono::{DateTime, Utc};
+use chrono::{TimeZone, Timelike, Utc};
 use url::Url;
 
 #[test]
@@ -8,201 +8,4170 @@ fn test_serialize_and_deserialize() {
         .trim()
         .replace("\r\n", "\n");
 
+    let sitemap = Sitemap {
+        pages: vec![Page::new(Url::parse("https://example.com").unwrap())
+            .with_lastmod(Utc.timestamp_opt(61, 0).unwrap())
+            .with_hash("0123456789abcdef0123456789abcdef")],
+    };
+
+    let mut buf = std::io::BufWriter::new(Vec::new());
+    sitemap.serialize(&mut buf).unwrap();
+    let serialized = String::from_utf8(buf.into_inner().unwrap()).unwrap();
+    let deserialized_from_rust = Sitemap::deserialize(serialized.as_bytes()).unwrap();
+    let mut deserialized_from_original =
+        Sitemap::deserialize(str_representation.as_bytes()).unwrap();
+
+    let str_representation_trimmed = include_str!("data/simple-sitemap-trimmed.xml")
+        .trim()
+        .replace("\r\n", "\n");
+
+    pretty_assertions::assert_eq!(
+        deserialized_from_original.pages[0].extra_meta,
+        [
+            Meta {
+                name: "description".into(),
+                content: "This is site description.".into(),
+            },
+            Meta {
+                name: "author".into(),
+                content: "Alice".into(),
+            },
+        ]
+    );
+    deserialized_from_original.pages[0].extra_meta.clear();
+    pretty_assertions::assert_eq!(deserialized_from_original, sitemap);
+    pretty_assertions::assert_eq!(serialized, str_representation_trimmed);
+    pretty_assertions::assert_eq!(deserialized_from_rust, sitemap);
+}
+
+#[test]
+fn test_lastmod_offset() {
+    let document = include_str!("data/simple-sitemap-trimmed.xml")
+        .trim()
+        .replace("1970-01-01T00:01:01Z", "1970-01-01T02:01:01+02:00");
+
+    let sitemap = Sitemap::deserialize(document.as_bytes()).unwrap();
+    let lastmod = sitemap.pages[0].lastmod.unwrap();
+    pretty_assertions::assert_eq!(lastmod, Utc.timestamp_opt(61, 0).unwrap());
+    pretty_assertions::assert_eq!(lastmod.offset().local_minus_utc(), 2 * 60 * 60);
+
+    let mut buf = vec![];
+    sitemap.serialize(&mut buf).unwrap();
+    pretty_assertions::assert_eq!(String::from_utf8(buf).unwrap(), document);
+}
+
+#[test]
+fn test_lastmod_precision() {
+    let sitemap = Sitemap {
+        pages: vec![Page::new(Url::parse("https://example.com").unwrap())
+            .with_lastmod(Utc.timestamp_millis_opt(61_250).unwrap())],
+    };
+
+    for (precision, lastmod, expected) in [
+        (LastmodPrecision::DateOnly, "1970-01-01", 0),
+        (LastmodPrecision::Seconds, "1970-01-01T00:01:01Z", 61_000),
+        (LastmodPrecision::Millis, "1970-01-01T00:01:01.250Z", 61_250),
+    ] {
+        let options = SerializeOptions {
+            lastmod_precision: precision,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        sitemap.serialize_with_options(&mut buf, &options).unwrap();
+        let serialized = String::from_utf8(buf).unwrap();
+        assert!(serialized.contains(&format!("<lastmod>{lastmod}</lastmod>")));
+
+        let deserialized = Sitemap::deserialize(serialized.as_bytes()).unwrap();
+        pretty_assertions::assert_eq!(
+            deserialized.pages[0].lastmod.unwrap(),
+            Utc.timestamp_millis_opt(expected).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_lastmod_formats() {
+    let document = include_str!("data/simple-sitemap-trimmed.xml").trim();
+    let lastmod = |value: &str| {
+        let document = document.replace("1970-01-01T00:01:01Z", value);
+        Sitemap::deserialize(document.as_bytes()).map(|sitemap| sitemap.pages[0].lastmod)
+    };
+    let utc = |hour| {
+        Some(
+            Utc.with_ymd_and_hms(2023, 1, 15, hour, 0, 0)
+                .unwrap()
+                .fixed_offset(),
+        )
+    };
+
+    pretty_assertions::assert_eq!(lastmod("2023-01-15"), Ok(utc(0)));
+    pretty_assertions::assert_eq!(lastmod("2023-01-15T10:00:00Z"), Ok(utc(10)));
+    pretty_assertions::assert_eq!(lastmod("2023-01-15T10:00:00"), Ok(utc(10)));
+    assert!(lastmod("15 January 2023").is_err());
+}
+
+#[test]
+fn test_naive_offset() {
+    let document = include_str!("data/simple-sitemap-trimmed.xml").trim();
+    let lastmod = |value: &str, options: &DeserializeOptions| {
+        let document = document.replace("1970-01-01T00:01:01Z", value);
+        Sitemap::deserialize_with_options(document.as_bytes(), options)
+            .unwrap()
+            .pages[0]
+            .lastmod
+            .unwrap()
+    };
+    let plus_five = chrono::FixedOffset::east_opt(5 * 3600).unwrap();
+    let options = DeserializeOptions {
+        naive_offset: plus_five,
+        ..Default::default()
+    };
+
+    let naive = "2023-01-15T10:00:00";
+    pretty_assertions::assert_eq!(
+        lastmod(naive, &DeserializeOptions::default()),
+        Utc.with_ymd_and_hms(2023, 1, 15, 10, 0, 0).unwrap()
+    );
+    let assumed = lastmod(naive, &options);
+    pretty_assertions::assert_eq!(
+        assumed,
+        plus_five.with_ymd_and_hms(2023, 1, 15, 10, 0, 0).unwrap()
+    );
+    pretty_assertions::assert_eq!(assumed.offset(), &plus_five);
+
+    // Explicit offsets and dates without a time are not affected.
+    pretty_assertions::assert_eq!(
+        lastmod("2023-01-15T10:00:00Z", &options),
+        Utc.with_ymd_and_hms(2023, 1, 15, 10, 0, 0).unwrap()
+    );
+    pretty_assertions::assert_eq!(
+        lastmod("2023-01-15", &options),
+        Utc.with_ymd_and_hms(2023, 1, 15, 0, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_indentation() {
+    let sitemap = Sitemap::deserialize(include_str!("data/simple-sitemap.xml").as_bytes()).unwrap();
+
+    let minified = SerializeOptions {
+        indent: false,
+        ..Default::default()
+    };
+    let mut buf = vec![];
+    sitemap.serialize_with_options(&mut buf, &minified).unwrap();
+    let xml = String::from_utf8(buf).unwrap();
+    assert!(!xml.contains('\n'));
+    assert!(!xml.contains(">  <") && !xml.contains("> <"));
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(&xml).unwrap(), sitemap);
+    more_asserts::assert_lt!(xml.len(), sitemap.to_xml_string().unwrap().len());
+
+    let tabs = SerializeOptions {
+        indent_string: "\t".to_string(),
+        ..Default::default()
+    };
+    let mut buf = vec![];
+    sitemap.serialize_with_options(&mut buf, &tabs).unwrap();
+    let xml = String::from_utf8(buf).unwrap();
+    assert!(xml.contains("\n\t<url>\n\t\t<loc>"));
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(&xml).unwrap(), sitemap);
+}
+
+#[test]
+fn test_stylesheet() {
+    let sitemap = Sitemap {
+        pages: vec![Page::new(Url::parse("https://example.com/").unwrap())],
+    };
+    let options = SerializeOptions {
+        stylesheet: Some("/sitemap.xsl?v=1&theme=\"dark\"".to_string()),
+        ..Default::default()
+    };
+
+    let mut buf = vec![];
+    sitemap.serialize_with_options(&mut buf, &options).unwrap();
+    let xml = String::from_utf8(buf).unwrap();
+    pretty_assertions::assert_eq!(
+        xml.lines().take(3).collect::<Vec<_>>(),
+        [
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            r#"<?xml-stylesheet type="text/xsl" href="/sitemap.xsl?v=1&amp;theme=&quot;dark&quot;"?>"#,
+            r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9" xmlns:xhtml="http://www.w3.org/1999/xhtml">"#,
+        ]
+    );
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(&xml).unwrap(), sitemap);
+    assert!(!sitemap.to_xml_string().unwrap().contains("xml-stylesheet"));
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?xml-stylesheet type="text/xsl" href="//example.com/main-sitemap.xsl"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/</loc>
+  </url>
+</urlset>"#;
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(xml).unwrap(), sitemap);
+}
+
+#[test]
+fn test_display() {
+    let sitemap = Sitemap::deserialize(include_str!("data/simple-sitemap.xml").as_bytes()).unwrap();
+
+    let displayed = sitemap.to_string();
+    pretty_assertions::assert_eq!(displayed, sitemap.to_xml_string().unwrap());
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(&displayed).unwrap(), sitemap);
+}
+
+#[test]
+fn test_stream_pages() {
+    let xml = include_str!("data/old-sitemap.xml");
+    let sitemap = Sitemap::from_xml_str(xml).unwrap();
+    let streamed = Sitemap::stream_pages(xml.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    pretty_assertions::assert_eq!(streamed, sitemap.pages);
+
+    let sitemap = Sitemap {
+        pages: vec![Page {
+            images: vec![ImageEntry {
+                loc: Url::parse("https://example.com/image.png").unwrap(),
+                title: Some("Image".to_string()),
+                caption: None,
+            }],
+            ..Page::new(Url::parse("https://example.com/").unwrap())
+                .with_lastmod(Utc.with_ymd_and_hms(2023, 8, 13, 11, 30, 46).unwrap())
+                .with_changefreq(ChangeFreq::Daily)
+                .with_hash("0123456789abcdef0123456789abcdef")
+        }],
+    };
+    let options = SerializeOptions {
+        hash_storage: HashStorage::Comment,
+        ..Default::default()
+    };
+    let mut buf = vec![];
+    sitemap.serialize_with_options(&mut buf, &options).unwrap();
+    let streamed = Sitemap::stream_pages(&buf[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    pretty_assertions::assert_eq!(streamed, sitemap.pages);
+
+    let mut index = Sitemap::stream_pages(
+        r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"></sitemapindex>"#
+            .as_bytes(),
+    );
+    pretty_assertions::assert_eq!(
+        index.next(),
+        Some(Err(
+            "failed to deserialize: unexpected root element <sitemapindex>".to_string()
+        ))
+    );
+    pretty_assertions::assert_eq!(index.next(), None);
+}
+
+#[test]
+fn test_xml_declaration() {
+    let sitemap = Sitemap {
+        pages: vec![Page::new(Url::parse("https://example.com/").unwrap())],
+    };
+    let serialize = |options: &SerializeOptions| {
+        let mut buf = vec![];
+        sitemap.serialize_with_options(&mut buf, options).unwrap();
+        assert!(!buf.starts_with(b"\xEF\xBB\xBF"));
+        pretty_assertions::assert_eq!(Sitemap::deserialize(&buf[..]).unwrap(), sitemap);
+        buf
+    };
+
+    let xml = serialize(&SerializeOptions::default());
+    assert!(xml.starts_with(br#"<?xml version="1.0" encoding="utf-8"?>"#));
+
+    let options = SerializeOptions {
+        xml_declaration: false,
+        ..Default::default()
+    };
+    assert!(serialize(&options).starts_with(b"<urlset "));
+    let options = SerializeOptions {
+        xml_declaration: false,
+        stylesheet: Some("/sitemap.xsl".to_string()),
+        generator_comment: Some("generated by auto_sitemap".to_string()),
+        ..Default::default()
+    };
+    let xml = String::from_utf8(serialize(&options)).unwrap();
+    assert!(xml.starts_with("<!-- generated by auto_sitemap at "));
+    assert!(!xml.contains("<?xml "));
+}
+
+#[test]
+fn test_generator_comment() {
+    let sitemap = Sitemap {
+        pages: vec![Page::new(Url::parse("https://example.com/").unwrap())],
+    };
+    let options = SerializeOptions {
+        generator_comment: Some("generated by auto_sitemap -- nightly".to_string()),
+        ..Default::default()
+    };
+
+    let start = Utc::now();
+    let mut buf = vec![];
+    sitemap.serialize_with_options(&mut buf, &options).unwrap();
+    let xml = String::from_utf8(buf).unwrap();
+    pretty_assertions::assert_eq!(
+        xml.lines().next().unwrap(),
+        r#"<?xml version="1.0" encoding="utf-8"?>"#
+    );
+    let comment = xml.lines().nth(1).unwrap();
+    let time = comment
+        .strip_prefix("<!-- generated by auto_sitemap - - nightly at ")
+        .and_then(|rest| rest.strip_suffix(" -->"))
+        .unwrap();
+    more_asserts::assert_ge!(
+        chrono::DateTime::parse_from_rfc3339(time).unwrap(),
+        start.with_nanosecond(0).unwrap()
+    );
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(&xml).unwrap(), sitemap);
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- generated by some other tool -->
+<!-- on a Tuesday -->
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <!-- home page -->
+  <url>
+    <loc>https://example.com/</loc>
+  </url>
+</urlset>"#;
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(xml).unwrap(), sitemap);
+}
+
+#[test]
+fn test_unknown_elements() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9" xmlns:image="http://www.google.com/schemas/sitemap-image/1.1" xmlns:mobile="http://www.google.com/schemas/sitemap-mobile/1.0" xmlns:custom="https://example.com/schemas/custom">
+  <custom:generator version="2">Some CMS</custom:generator>
+  <url>
+    <loc>https://example.com/</loc>
+    <mobile:mobile/>
+    <lastmod>2023-08-13T11:30:46Z</lastmod>
+    <image:image>
+      <image:loc>https://example.com/cat.jpg</image:loc>
+      <image:license>https://example.com/license</image:license>
+    </image:image>
+    <custom:rating><custom:stars>5</custom:stars></custom:rating>
+    <unmodelled>value</unmodelled>
+  </url>
+  <url>
+    <loc>https://example.com/about</loc>
+  </url>
+</urlset>"#;
+
+    let sitemap = Sitemap::from_xml_str(xml).unwrap();
+    pretty_assertions::assert_eq!(
+        sitemap.pages,
+        [
+            Page {
+                images: vec![ImageEntry {
+                    loc: Url::parse("https://example.com/cat.jpg").unwrap(),
+                    title: None,
+                    caption: None,
+                }],
+                ..Page::new(Url::parse("https://example.com/").unwrap())
+                    .with_lastmod(Utc.with_ymd_and_hms(2023, 8, 13, 11, 30, 46).unwrap())
+            },
+            Page::new(Url::parse("https://example.com/about").unwrap()),
+        ]
+    );
+}
+
+#[test]
+fn test_min_lastmod() {
+    let old = Page::new(Url::parse("https://example.com/old").unwrap())
+        .with_lastmod(Utc.with_ymd_and_hms(2019, 12, 31, 23, 59, 59).unwrap());
+    let recent = Page::new(Url::parse("https://example.com/recent").unwrap())
+        .with_lastmod(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
+    let sitemap = Sitemap {
+        pages: vec![old.clone(), recent.clone()],
+    };
+
+    let options = SerializeOptions {
+        min_lastmod: Some(Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap()),
+        ..Default::default()
+    };
+    let mut buf = vec![];
+    sitemap.serialize_with_options(&mut buf, &options).unwrap();
+    let xml = String::from_utf8(buf).unwrap();
+    assert!(!xml.contains("2019-12-31"));
+    pretty_assertions::assert_eq!(
+        Sitemap::from_xml_str(&xml).unwrap().pages,
+        [
+            Page {
+                lastmod: None,
+                ..old
+            },
+            recent
+        ]
+    );
+}
+
+#[test]
+fn test_hash_meta_name() {
+    let sitemap = Sitemap {
+        pages: vec![Page::new(Url::parse("https://example.com").unwrap())
+            .with_hash("0123456789abcdef0123456789abcdef")],
+    };
+
+    let mut buf = vec![];
+    let options = SerializeOptions {
+        hash_meta_name: "example:hash".into(),
+        ..Default::default()
+    };
+    sitemap.serialize_with_options(&mut buf, &options).unwrap();
+    let serialized = String::from_utf8(buf).unwrap();
+    assert!(serialized.contains(r#"name="example:hash""#));
+
+    let options = DeserializeOptions {
+        hash_meta_name: "example:hash".into(),
+        ..Default::default()
+    };
+    let deserialized = Sitemap::deserialize_with_options(serialized.as_bytes(), &options).unwrap();
+    pretty_assertions::assert_eq!(deserialized, sitemap);
+
+    let deserialized = Sitemap::deserialize(serialized.as_bytes()).unwrap();
+    pretty_assertions::assert_eq!(deserialized.pages[0].md5_hash, None);
+}
+
+#[test]
+fn test_hash_storage() {
+    let sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("https://example.com/").unwrap())
+                .with_hash("0123456789abcdef0123456789abcdef")
+                .with_etag("\"v1\""),
+            Page::new(Url::parse("https://example.com/about").unwrap()),
+            Page::new(Url::parse("https://example.com/blog").unwrap())
+                .with_hash("fedcba9876543210fedcba9876543210"),
+        ],
+    };
+    let serialize = |hash_storage: HashStorage| {
+        let options = SerializeOptions {
+            hash_storage,
+            ..Default::default()
+        };
+        let mut buf = vec![];
+        sitemap.serialize_with_options(&mut buf, &options).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+
+    let xml = serialize(HashStorage::Meta);
+    assert!(xml.contains(r#"name="auto_sitemap_md5_hash""#));
+    assert!(!xml.contains("<!--"));
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(&xml).unwrap(), sitemap);
+
+    let xml = serialize(HashStorage::Comment);
+    assert!(xml.contains("<!-- auto_sitemap_md5_hash: 0123456789abcdef0123456789abcdef -->"));
+    assert!(!xml.contains(r#"name="auto_sitemap_md5_hash""#));
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(&xml).unwrap(), sitemap);
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <!-- auto_sitemap_md5_hash: 00000000000000000000000000000000 -->
+  <url>
+    <loc>https://example.com/</loc>
+    <!-- unrelated comment -->
+  </url>
+</urlset>"#;
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(xml).unwrap().pages[0].md5_hash, None);
+
+    // The comment belongs to the `<url>` it is in, wherever it is placed.
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/</loc>
+  </url>
+  <url>
+    <loc>https://example.com/about</loc>
+    <lastmod>2023-05-10T00:00:00Z</lastmod>
+    <!-- auto_sitemap_md5_hash: 0123456789abcdef0123456789abcdef -->
+  </url>
+</urlset>"#;
+    let pages = Sitemap::from_xml_str(xml).unwrap().pages;
+    pretty_assertions::assert_eq!(pages[0].md5_hash, None);
+    pretty_assertions::assert_eq!(
+        pages[1].md5_hash.as_deref(),
+        Some("0123456789abcdef0123456789abcdef")
+    );
+}
+
+#[test]
+fn test_extra_meta() {
+    let sitemap = Sitemap {
+        pages: vec![Page {
+            extra_meta: vec![
+                Meta {
+                    name: "author".into(),
+                    content: "Jane".into(),
+                },
+                Meta {
+                    name: "section".into(),
+                    content: "news".into(),
+                },
+            ],
+            ..Page::new(Url::parse("https://example.com").unwrap())
+                .with_hash("0123456789abcdef0123456789abcdef")
+        }],
+    };
+
+    let serialized = sitemap.to_xml_string().unwrap();
+    pretty_assertions::assert_eq!(serialized.matches("<xhtml:meta").count(), 3);
+
+    let deserialized = Sitemap::from_xml_str(&serialized).unwrap();
+    pretty_assertions::assert_eq!(deserialized, sitemap);
+}
+
+#[test]
+fn test_alternates() {
+    let sitemap = Sitemap {
+        pages: vec![Page {
+            alternates: vec![
+                Alternate {
+                    href: Url::parse("https://m.example.com/page").unwrap(),
+                    hreflang: None,
+                    media: Some("only screen and (max-width: 640px)".into()),
+                },
+                Alternate {
+                    href: Url::parse("https://example.com/de/page").unwrap(),
+                    hreflang: Some("de".into()),
+                    media: None,
+                },
+            ],
+            ..Page::new(Url::parse("https://example.com/page").unwrap())
+        }],
+    };
+
+    let serialized = sitemap.to_xml_string().unwrap();
+    assert!(serialized.contains(
+        r#"<xhtml:link rel="alternate" media="only screen and (max-width: 640px)" href="https://m.example.com/page" />"#
+    ));
+    assert!(serialized.contains(
+        r#"<xhtml:link rel="alternate" hreflang="de" href="https://example.com/de/page" />"#
+    ));
+    pretty_assertions::assert_eq!(Sitemap::from_xml_str(&serialized).unwrap(), sitemap);
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9" xmlns:xhtml="http://www.w3.org/1999/xhtml">
+  <url>
+    <loc>https://example.com/page</loc>
+    <xhtml:link rel="alternate" hreflang="en" media="only screen and (max-width: 640px)" href="https://m.example.com/page"/>
+    <xhtml:link rel="canonical" href="https://example.com/page"/>
+  </url>
+</urlset>"#;
+    pretty_assertions::assert_eq!(
+        Sitemap::from_xml_str(xml).unwrap().pages[0].alternates,
+        [Alternate {
+            href: Url::parse("https://m.example.com/page").unwrap(),
+            hreflang: Some("en".into()),
+            media: Some("only screen and (max-width: 640px)".into()),
+        }]
+    );
+}
+
+#[test]
+fn test_images() {
+    let sitemap = Sitemap {
+        pages: vec![Page {
+            images: vec![
+                ImageEntry {
+                    loc: Url::parse("https://example.com/cat.jpg").unwrap(),
+                    title: Some("Cat".into()),
+                    caption: Some("A cat on a mat".into()),
+                },
+                ImageEntry {
+                    loc: Url::parse("https://example.com/dog.png").unwrap(),
+                    title: None,
+                    caption: None,
+                },
+            ],
+            ..Page::new(Url::parse("https://example.com/gallery").unwrap())
+        }],
+    };
+
+    let mut buf = vec![];
+    sitemap.serialize(&mut buf).unwrap();
+    let serialized = String::from_utf8(buf).unwrap();
+    assert!(serialized.contains(r#"xmlns:image="http://www.google.com/schemas/sitemap-image/1.1""#));
+    assert!(serialized.contains("<image:loc>https://example.com/cat.jpg</image:loc>"));
+    assert!(serialized.contains("<image:caption>A cat on a mat</image:caption>"));
+
+    let deserialized = Sitemap::deserialize(serialized.as_bytes()).unwrap();
+    pretty_assertions::assert_eq!(deserialized, sitemap);
+}
+
+#[test]
+fn test_videos() {
     let sitemap = Sitemap {
         pages: vec![Page {
-            url: Url::parse("https://example.com").unwrap(),
-            lastmod: Some(DateTime::<Utc>::from_utc(
-                chrono::NaiveDateTime::from_timestamp_opt(61, 0).unwrap(),
-                Utc,
-            )),
-            md5_hash: Some("0123456789abcdef0123456789abcdef".into()),
+            videos: vec![VideoEntry {
+                thumbnail_loc: Some(Url::parse("https://example.com/talk.jpg").unwrap()),
+                title: "Talk".into(),
+                description: "Recording of the talk".into(),
+                content_loc: Some(Url::parse("https://example.com/talk.mp4").unwrap()),
+                player_loc: Some(Url::parse("https://example.com/player?video=talk").unwrap()),
+                duration: Some(600),
+            }],
+            ..Page::new(Url::parse("https://example.com/talk").unwrap())
+        }],
+    };
+
+    let mut buf = vec![];
+    sitemap.serialize(&mut buf).unwrap();
+    let serialized = String::from_utf8(buf).unwrap();
+    assert!(serialized.contains(r#"xmlns:video="http://www.google.com/schemas/sitemap-video/1.1""#));
+    assert!(!serialized.contains("xmlns:image"));
+    assert!(serialized.contains("<video:duration>600</video:duration>"));
+
+    let deserialized = Sitemap::deserialize(serialized.as_bytes()).unwrap();
+    pretty_assertions::assert_eq!(deserialized, sitemap);
+
+    let without_title = serialized.replace("<video:title>Talk</video:title>", "");
+    assert!(Sitemap::deserialize(without_title.as_bytes()).is_err());
+}
+
+#[test]
+fn test_news() {
+    let sitemap = Sitemap {
+        pages: vec![
+            Page {
+                news: Some(NewsEntry {
+                    publication_name: "Example Times".into(),
+                    publication_language: "en".into(),
+                    publication_date: Utc.timestamp_opt(61, 0).unwrap(),
+                    title: "Launch day".into(),
+                }),
+                ..Page::new(Url::parse("https://example.com/news/launch").unwrap())
+            },
+            Page::new(Url::parse("https://example.com/about").unwrap()),
+        ],
+    };
+
+    let mut buf = vec![];
+    sitemap.serialize(&mut buf).unwrap();
+    let serialized = String::from_utf8(buf).unwrap();
+    assert!(
+        serialized.contains("<news:publication_date>1970-01-01T00:01:01Z</news:publication_date>")
+    );
+    pretty_assertions::assert_eq!(serialized.matches("<news:news>").count(), 1);
+    assert!(serialized.contains(r#"xmlns:news="http://www.google.com/schemas/sitemap-news/0.9""#));
+
+    let deserialized = Sitemap::deserialize(serialized.as_bytes()).unwrap();
+    pretty_assertions::assert_eq!(deserialized, sitemap);
+}
+
+#[test]
+fn test_serialized_size() {
+    let url = |path: &str| Url::parse(&format!("https://example.com{}", path)).unwrap();
+    let assert_sizes = |sitemap: &Sitemap| {
+        let size = sitemap.to_xml_string().unwrap().len();
+        pretty_assertions::assert_eq!(sitemap.serialized_size(), Ok(size));
+        pretty_assertions::assert_eq!(sitemap.approx_serialized_size(), size);
+    };
+
+    // Without extensions, whose namespaces are then not declared.
+    let mut sitemap = Sitemap::from_xml_str(include_str!("data/old-sitemap.xml")).unwrap();
+    assert_sizes(&sitemap);
+
+    // With every optional field.
+    let lastmod = chrono::FixedOffset::east_opt(2 * 60 * 60)
+        .unwrap()
+        .timestamp_opt(61, 0)
+        .unwrap();
+    sitemap.pages.push(Page {
+        images: vec![ImageEntry {
+            loc: url("/cat.jpg"),
+            title: Some("Cat".into()),
+            caption: Some("A cat".into()),
+        }],
+        videos: vec![VideoEntry {
+            thumbnail_loc: Some(url("/talk.jpg")),
+            title: "Talk".into(),
+            description: "Recording of the talk".into(),
+            content_loc: Some(url("/talk.mp4")),
+            player_loc: Some(url("/player")),
+            duration: Some(600),
+        }],
+        news: Some(NewsEntry {
+            publication_name: "Example Times".into(),
+            publication_language: "en".into(),
+            publication_date: Utc.timestamp_opt(61, 0).unwrap(),
+            title: "Launch day".into(),
+        }),
+        alternates: vec![
+            Alternate {
+                href: url("/de/talk"),
+                hreflang: Some("de".into()),
+                media: None,
+            },
+            Alternate {
+                href: url("/m/talk"),
+                hreflang: None,
+                media: Some("only screen and (max-width: 640px)".into()),
+            },
+        ],
+        extra_meta: vec![Meta {
+            name: "author".into(),
+            content: "Jane".into(),
         }],
+        ..Page::new(url("/talk"))
+            .with_lastmod(lastmod)
+            .with_changefreq(ChangeFreq::Weekly)
+            .with_priority(0.8)
+            .with_hash("0123456789abcdef0123456789abcdef")
+            .with_etag("v1")
+    });
+    assert_sizes(&sitemap);
+
+    assert_sizes(&Sitemap { pages: vec![] });
+}
+
+#[test]
+fn test_generate_from_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |path: &str, contents: &str| {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(61))
+            .unwrap();
+    };
+    write("index.html", "<html>home</html>");
+    write("about/index.html", "<html>about</html>");
+    write("blog/first post.htm", "<html>post</html>");
+    write("blog/style.css", "body {}");
+    write("images/cat.png", "");
+
+    let base_url = Url::parse("https://example.com/docs/").unwrap();
+    let sitemap = Sitemap::generate_from_directory(dir.path(), &base_url).unwrap();
+
+    let lastmod = Utc.timestamp_opt(61, 0).unwrap();
+    let page = |path: &str, html: &str| {
+        let mut page = Page::new(base_url.join(path).unwrap())
+            .with_lastmod(lastmod)
+            .with_hash(HashMode::Raw.fingerprint(html));
+        page.content_length = Some(html.len());
+        page
+    };
+    pretty_assertions::assert_eq!(
+        sitemap.pages,
+        [
+            page("", "<html>home</html>"),
+            page("about/", "<html>about</html>"),
+            page("blog/first%20post.htm", "<html>post</html>"),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_generate_from_directory_symlinks_and_case() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("guide")).unwrap();
+    std::fs::write(dir.path().join("guide/INDEX.HTML"), "<html>guide</html>").unwrap();
+    std::fs::write(dir.path().join("guide/Intro.Htm"), "<html>intro</html>").unwrap();
+    // Neither a link back up the tree nor a link to a file is followed.
+    std::os::unix::fs::symlink(dir.path(), dir.path().join("guide/loop")).unwrap();
+    std::os::unix::fs::symlink(
+        dir.path().join("guide/Intro.Htm"),
+        dir.path().join("guide/copy.html"),
+    )
+    .unwrap();
+
+    let base_url = Url::parse("https://example.com/").unwrap();
+    let sitemap = Sitemap::generate_from_directory(dir.path(), &base_url).unwrap();
+
+    pretty_assertions::assert_eq!(
+        sitemap
+            .pages
+            .iter()
+            .map(|page| page.url.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "https://example.com/guide/",
+            "https://example.com/guide/Intro.Htm"
+        ]
+    );
+}
+
+#[test]
+fn test_lastmod_from_git() {
+    let repo = std::env::temp_dir().join(format!("auto_sitemap_git_{}", std::process::id()));
+    std::fs::create_dir_all(&repo).unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .env("GIT_COMMITTER_DATE", "1970-01-01T00:01:01Z")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "--quiet"]);
+    std::fs::write(repo.join("index.html"), "<html></html>").unwrap();
+    std::fs::write(repo.join("draft.html"), "<html></html>").unwrap();
+    git(&["add", "index.html"]);
+    git(&["commit", "--quiet", "-m", "Add index"]);
+
+    let page = |path: &str| {
+        Page::new(
+            Url::parse("https://example.com/")
+                .unwrap()
+                .join(path)
+                .unwrap(),
+        )
+    };
+    let mut sitemap = Sitemap {
+        pages: vec![page("/"), page("/draft"), page("/external")],
+    };
+    let result = sitemap.set_lastmod_from_git(&repo, |url| match url.path() {
+        "/" => Some("index.html".into()),
+        "/draft" => Some("draft.html".into()),
+        _ => None,
+    });
+    std::fs::remove_dir_all(&repo).unwrap();
+    result.unwrap();
+
+    pretty_assertions::assert_eq!(
+        sitemap
+            .pages
+            .iter()
+            .map(|page| page.lastmod)
+            .collect::<Vec<_>>(),
+        [
+            Some(Utc.timestamp_opt(61, 0).unwrap().fixed_offset()),
+            None,
+            None
+        ]
+    );
+}
+
+#[test]
+fn test_normalize_urls() {
+    let page = |url: &str, lastmod: Option<i64>| Page {
+        lastmod: lastmod.map(|secs| Utc.timestamp_opt(secs, 0).unwrap().fixed_offset()),
+        ..Page::new(Url::parse(url).unwrap())
+    };
+    let mut sitemap = Sitemap {
+        pages: vec![
+            page("https://example.com/page", Some(200)),
+            page("https://example.com/page/", Some(100)),
+            page("https://example.com/page?ref=x", None),
+            page("https://example.com/other?ref=x&id=1", None),
+            page("https://example.com/", Some(300)),
+        ],
+    };
+
+    let unchanged = sitemap.clone();
+    sitemap
+        .normalize_urls(&NormalizeOptions::default())
+        .unwrap();
+    pretty_assertions::assert_eq!(sitemap, unchanged);
+
+    let options = NormalizeOptions {
+        trailing_slash: TrailingSlash::Never,
+        lowercase_host: true,
+        remove_default_port: true,
+        drop_query_params: vec!["ref".into()],
+    };
+    sitemap.normalize_urls(&options).unwrap();
+    pretty_assertions::assert_eq!(
+        sitemap.pages,
+        [
+            page("https://example.com/page", Some(100)),
+            page("https://example.com/other?id=1", None),
+            page("https://example.com/", Some(300)),
+        ]
+    );
+}
+
+#[test]
+fn test_trailing_slash() {
+    let page = |url: &str| Page::new(Url::parse(url).unwrap());
+    let sitemap = Sitemap {
+        pages: vec![
+            page("https://example.com/"),
+            page("https://example.com/about"),
+            page("https://example.com/about/"),
+            page("https://example.com/blog/"),
+            page("https://example.com/blog/post?id=1"),
+            page("https://example.com/docs//"),
+            page("https://example.com/feed.xml"),
+        ],
+    };
+    let normalize = |trailing_slash: TrailingSlash| {
+        let mut sitemap = sitemap.clone();
+        let options = NormalizeOptions {
+            trailing_slash,
+            ..Default::default()
+        };
+        sitemap.normalize_urls(&options).unwrap();
+        sitemap.into_urls()
+    };
+
+    pretty_assertions::assert_eq!(
+        normalize(TrailingSlash::Preserve),
+        sitemap.clone().into_urls()
+    );
+    pretty_assertions::assert_eq!(
+        normalize(TrailingSlash::Always),
+        [
+            "https://example.com/",
+            "https://example.com/about/",
+            "https://example.com/blog/",
+            "https://example.com/blog/post/?id=1",
+            "https://example.com/docs//",
+            "https://example.com/feed.xml",
+        ]
+        .map(|url| Url::parse(url).unwrap())
+    );
+    pretty_assertions::assert_eq!(
+        normalize(TrailingSlash::Never),
+        [
+            "https://example.com/",
+            "https://example.com/about",
+            "https://example.com/blog",
+            "https://example.com/blog/post?id=1",
+            "https://example.com/docs",
+            "https://example.com/feed.xml",
+        ]
+        .map(|url| Url::parse(url).unwrap())
+    );
+}
+
+#[test]
+fn test_dedup() {
+    let page = |url: &str, lastmod: Option<i64>, hash: &str| Page {
+        lastmod: lastmod.map(|secs| Utc.timestamp_opt(secs, 0).unwrap().fixed_offset()),
+        ..Page::new(Url::parse(url).unwrap()).with_hash(hash)
+    };
+    let mut sitemap = Sitemap {
+        pages: vec![
+            page("https://example.com/a", Some(100), "old"),
+            page("https://example.com/b", None, "b"),
+            page("https://example.com/a", Some(200), "new"),
+        ],
+    };
+
+    sitemap.dedup();
+    pretty_assertions::assert_eq!(
+        sitemap.pages,
+        [
+            page("https://example.com/a", Some(200), "new"),
+            page("https://example.com/b", None, "b"),
+        ]
+    );
+}
+
+#[test]
+fn test_find_duplicates() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://example.com/a</loc></url>
+  <url><loc>https://example.com/b</loc></url>
+  <url><loc>https://example.com/a</loc></url>
+  <url><loc>https://example.com/c</loc></url>
+  <url><loc>https://example.com/b</loc></url>
+  <url><loc>https://example.com/a</loc></url>
+</urlset>"#;
+
+    let sitemap = Sitemap::from_xml_str(xml).unwrap();
+    pretty_assertions::assert_eq!(sitemap.pages.len(), 6);
+    pretty_assertions::assert_eq!(
+        sitemap.find_duplicates(),
+        [
+            Url::parse("https://example.com/a").unwrap(),
+            Url::parse("https://example.com/b").unwrap(),
+        ]
+    );
+
+    let options = DeserializeOptions {
+        reject_duplicates: true,
+        ..Default::default()
+    };
+    pretty_assertions::assert_eq!(
+        Sitemap::deserialize_with_options(xml.as_bytes(), &options).unwrap_err(),
+        "failed to deserialize: duplicate URLs: https://example.com/a, https://example.com/b"
+    );
+    let unique = include_str!("data/simple-sitemap.xml");
+    assert!(Sitemap::deserialize_with_options(unique.as_bytes(), &options).is_ok());
+}
+
+#[test]
+fn test_page_builder() {
+    let url = Url::parse("https://example.com/").unwrap();
+    let page = Page::new(url.clone())
+        .with_lastmod(Utc.timestamp_opt(61, 0).unwrap())
+        .with_hash("0123456789abcdef0123456789abcdef");
+
+    pretty_assertions::assert_eq!(page.url, url);
+    pretty_assertions::assert_eq!(
+        page.lastmod,
+        Some(Utc.timestamp_opt(61, 0).unwrap().fixed_offset())
+    );
+    pretty_assertions::assert_eq!(
+        page.md5_hash.as_deref(),
+        Some("0123456789abcdef0123456789abcdef")
+    );
+    assert!(page.images.is_empty() && page.videos.is_empty() && page.news.is_none());
+}
+
+#[test]
+fn test_iteration() {
+    let url = |path: &str| {
+        Url::parse("https://example.com/")
+            .unwrap()
+            .join(path)
+            .unwrap()
+    };
+    let mut sitemap = Sitemap {
+        pages: vec![Page::new(url("/a")), Page::new(url("/b"))],
     };
 
-    let mut buf = std::io::BufWriter::new(Vec::new());
-    sitemap.serialize(&mut buf).unwrap();
-    let serialized = String::from_utf8(buf.into_inner().unwrap()).unwrap();
-    let deserialized_from_rust = Sitemap::deserialize(serialized.as_bytes()).unwrap();
-    let deserialized_from_original = Sitemap::deserialize(str_representation.as_bytes()).unwrap();
+    pretty_assertions::assert_eq!(sitemap.len(), 2);
+    assert!(!sitemap.is_empty());
+    assert!(sitemap.contains(&url("/a")));
+    assert!(!sitemap.contains(&url("/c")));
+
+    for page in &mut sitemap {
+        page.md5_hash = Some("0123456789abcdef0123456789abcdef".into());
+    }
+    assert!(sitemap.iter().all(|page| page.md5_hash.is_some()));
+
+    let urls = (&sitemap)
+        .into_iter()
+        .map(|page| page.url.clone())
+        .collect::<Vec<_>>();
+    pretty_assertions::assert_eq!(urls, [url("/a"), url("/b")]);
+    pretty_assertions::assert_eq!(sitemap.into_iter().count(), 2);
+}
+
+#[test]
+fn test_urls() {
+    let sitemap = Sitemap::deserialize(include_str!("data/old-sitemap.xml").as_bytes()).unwrap();
+    let expected = sitemap
+        .pages
+        .iter()
+        .map(|page| page.url.clone())
+        .collect::<Vec<_>>();
+    more_asserts::assert_gt!(expected.len(), 1);
+
+    pretty_assertions::assert_eq!(sitemap.urls(), expected.iter().collect::<Vec<_>>());
+    pretty_assertions::assert_eq!(sitemap.into_urls(), expected);
+    pretty_assertions::assert_eq!(Sitemap { pages: vec![] }.urls(), Vec::<&Url>::new());
+}
+
+#[test]
+fn test_etag() {
+    let url = Url::parse("https://example.com/").unwrap();
+    let old_lastmod = Utc
+        .with_ymd_and_hms(2023, 5, 10, 0, 0, 0)
+        .unwrap()
+        .fixed_offset();
+    let old_sitemap = Sitemap {
+        pages: vec![Page::new(url.clone())
+            .with_lastmod(old_lastmod)
+            .with_hash("34ef8d45ba04c3a5b98f1a25c0a6ac7b")
+            .with_etag(r#""v1""#)],
+    };
+    pretty_assertions::assert_eq!(
+        Sitemap::from_xml_str(&old_sitemap.to_xml_string().unwrap()).unwrap(),
+        old_sitemap
+    );
+
+    let crawled = |etag: &str| Sitemap {
+        pages: vec![Page::new(url.clone())
+            .with_lastmod(Utc::now())
+            .with_hash("1f0e8893210f6496401d171ff77c7e92")
+            .with_etag(etag)],
+    };
+
+    let mut sitemap = crawled(r#""v1""#);
+    let info = sitemap.combine_with_old_sitemap(&old_sitemap).unwrap();
+    pretty_assertions::assert_eq!(info.unchanged_pages, vec![url.clone()]);
+    pretty_assertions::assert_eq!(sitemap.pages[0].lastmod, Some(old_lastmod));
+
+    let mut sitemap = crawled(r#""v2""#);
+    let info = sitemap.combine_with_old_sitemap(&old_sitemap).unwrap();
+    pretty_assertions::assert_eq!(info.updated_pages, [url]);
+    assert_ne!(sitemap.pages[0].lastmod, Some(old_lastmod));
+}
+
+#[test]
+fn test_changefreq_and_priority() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/</loc>
+    <lastmod>2023-05-10T00:00:00Z</lastmod>
+    <changefreq>daily</changefreq>
+    <priority>0.9</priority>
+  </url>
+  <url>
+    <loc>https://example.com/about</loc>
+    <changefreq>yearly</changefreq>
+  </url>
+</urlset>"#;
+    let old_sitemap = Sitemap::from_xml_str(xml).unwrap();
+    pretty_assertions::assert_eq!(
+        old_sitemap.pages,
+        [
+            Page::new(Url::parse("https://example.com/").unwrap())
+                .with_lastmod(Utc.with_ymd_and_hms(2023, 5, 10, 0, 0, 0).unwrap())
+                .with_changefreq(ChangeFreq::Daily)
+                .with_priority(0.9),
+            Page::new(Url::parse("https://example.com/about").unwrap())
+                .with_changefreq(ChangeFreq::Yearly),
+        ]
+    );
+    pretty_assertions::assert_eq!(
+        Sitemap::from_xml_str(&old_sitemap.to_xml_string().unwrap()).unwrap(),
+        old_sitemap
+    );
+    assert!(Sitemap::from_xml_str(&xml.replace("daily", "sometimes")).is_err());
+
+    let mut sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("https://example.com/").unwrap()).with_lastmod(Utc::now()),
+            Page::new(Url::parse("https://example.com/about").unwrap())
+                .with_changefreq(ChangeFreq::Monthly)
+                .with_priority(0.3),
+        ],
+    };
+    sitemap.combine_with_old_sitemap(&old_sitemap).unwrap();
+    pretty_assertions::assert_eq!(sitemap.pages[0].priority, Some(0.9));
+    pretty_assertions::assert_eq!(sitemap.pages[0].changefreq, Some(ChangeFreq::Daily));
+    pretty_assertions::assert_eq!(sitemap.pages[1].priority, Some(0.3));
+    pretty_assertions::assert_eq!(sitemap.pages[1].changefreq, Some(ChangeFreq::Monthly));
+}
+
+#[test]
+fn test_keep_missing() {
+    let lastmod = Utc.with_ymd_and_hms(2023, 5, 10, 0, 0, 0).unwrap();
+    let hash = "34ef8d45ba04c3a5b98f1a25c0a6ac7b";
+    let old_sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("https://example.com/").unwrap())
+                .with_lastmod(lastmod)
+                .with_hash(hash),
+            Page::new(Url::parse("https://example.com/dynamic").unwrap())
+                .with_lastmod(lastmod)
+                .with_hash(hash),
+        ],
+    };
+    let new_sitemap = Sitemap {
+        pages: vec![Page::new(Url::parse("https://example.com/").unwrap())
+            .with_lastmod(Utc::now())
+            .with_hash(hash)],
+    };
+
+    let mut sitemap = new_sitemap.clone();
+    let info = sitemap.combine_with_old_sitemap(&old_sitemap).unwrap();
+    pretty_assertions::assert_eq!(sitemap.len(), 1);
+    pretty_assertions::assert_eq!(info.removed_pages, [old_sitemap.pages[1].url.clone()]);
+    assert!(info.kept_pages.is_empty());
+
+    let mut sitemap = new_sitemap.clone();
+    let options = CombineOptions { keep_missing: true };
+    let info = sitemap
+        .combine_with_old_sitemap_with_options(&old_sitemap, &options)
+        .unwrap();
+    pretty_assertions::assert_eq!(sitemap, old_sitemap);
+    pretty_assertions::assert_eq!(info.kept_pages, [old_sitemap.pages[1].url.clone()]);
+    assert!(info.removed_pages.is_empty());
+    pretty_assertions::assert_eq!(info.unchanged_pages, [old_sitemap.pages[0].url.clone()]);
+    assert!(!info.has_changes());
+    pretty_assertions::assert_eq!(info.kept_count(), 1);
+    pretty_assertions::assert_eq!(
+        info.summary(),
+        "0 new, 0 removed, 0 updated, 1 unchanged, 1 kept"
+    );
+}
+
+#[test]
+fn test_update_report() {
+    let url = |path: &str| Url::parse(&format!("https://example.com{}", path)).unwrap();
+    let info = UpdateInfo {
+        new_pages: vec![url("/new")],
+        updated_pages: vec![url("/a"), url("/b")],
+        unchanged_pages: vec![url("/")],
+        removed_pages: vec![url("/old")],
+        kept_pages: vec![],
+    };
+
+    let report = info.report(false);
+    pretty_assertions::assert_eq!(
+        report.lines().collect::<Vec<_>>(),
+        [
+            "+ https://example.com/new",
+            "- https://example.com/old",
+            "~ https://example.com/a",
+            "~ https://example.com/b",
+            "1 new, 1 removed, 2 updated, 1 unchanged",
+        ]
+    );
+    assert!(!report.contains('\x1b'));
+
+    let colored = info.report(true);
+    assert!(colored.contains("\x1b[32m+ https://example.com/new\x1b[0m\n"));
+    assert!(colored.contains("\x1b[31m- https://example.com/old\x1b[0m\n"));
+}
+
+#[test]
+fn test_lastmod_overrides() {
+    let url = |path: &str| Url::parse(&format!("https://example.com{}", path)).unwrap();
+    let old_lastmod = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+    let new_lastmod = Utc.with_ymd_and_hms(2023, 6, 1, 12, 0, 0).unwrap();
+    let mut sitemap = Sitemap {
+        pages: vec![
+            Page::new(url("/")).with_lastmod(old_lastmod),
+            Page::new(url("/about")).with_lastmod(old_lastmod),
+            Page::new(url("/blog")),
+        ],
+    };
+
+    let overrides = [
+        (url("/about"), new_lastmod),
+        (url("/blog"), new_lastmod),
+        (url("/missing"), new_lastmod),
+    ]
+    .into_iter()
+    .collect();
+    let missing = sitemap.apply_lastmod_overrides(overrides);
+
+    pretty_assertions::assert_eq!(missing, [url("/missing")]);
+    pretty_assertions::assert_eq!(
+        sitemap
+            .pages
+            .iter()
+            .map(|page| page.lastmod)
+            .collect::<Vec<_>>(),
+        [
+            Some(old_lastmod.fixed_offset()),
+            Some(new_lastmod.fixed_offset()),
+            Some(new_lastmod.fixed_offset())
+        ]
+    );
+}
+
+#[test]
+fn test_update_base_url() {
+    let mut sitemap = Sitemap {
+        pages: [
+            "http://localhost:3000/old-base",
+            "http://localhost:3000/old-base/page?q=1",
+            "http://localhost:3000/old-base-2/page",
+            "http://localhost:3000/other",
+            "http://localhost:8000/old-base/page",
+            "http://example.org/old-base/page",
+        ]
+        .into_iter()
+        .map(|url| Page::new(Url::parse(url).unwrap()))
+        .collect(),
+    };
+
+    let untouched = sitemap
+        .update_base_url(
+            "http://localhost:3000/old-base/",
+            "https://example.com/new-base/",
+        )
+        .unwrap();
+    pretty_assertions::assert_eq!(
+        sitemap
+            .iter()
+            .map(|page| page.url.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "https://example.com/new-base",
+            "https://example.com/new-base/page?q=1",
+            "http://localhost:3000/old-base-2/page",
+            "http://localhost:3000/other",
+            "http://localhost:8000/old-base/page",
+            "http://example.org/old-base/page",
+        ]
+    );
+    pretty_assertions::assert_eq!(
+        untouched.iter().map(|url| url.as_str()).collect::<Vec<_>>(),
+        sitemap
+            .iter()
+            .skip(2)
+            .map(|page| page.url.as_str())
+            .collect::<Vec<_>>(),
+    );
+
+    let untouched = sitemap
+        .update_base_url("http://localhost:3000", "https://example.com")
+        .unwrap();
+    pretty_assertions::assert_eq!(untouched.len(), 4);
+    pretty_assertions::assert_eq!(sitemap.pages[3].url.as_str(), "https://example.com/other");
+
+    assert!(sitemap
+        .update_base_url("http://localhost:3000", "not a url")
+        .is_err());
+    assert!(sitemap
+        .update_base_url("http://localhost:3000", "ftp://example.com")
+        .is_err());
+}
+
+#[test]
+fn test_upgrade_to_https() {
+    let mut sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("http://example.com/").unwrap()),
+            Page::new(Url::parse("https://example.com/secure").unwrap()),
+            Page::new(Url::parse("http://example.com:8080/page?q=1").unwrap()),
+        ],
+    };
+    sitemap.upgrade_to_https();
+
+    pretty_assertions::assert_eq!(
+        sitemap
+            .iter()
+            .map(|page| page.url.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "https://example.com/",
+            "https://example.com/secure",
+            "https://example.com:8080/page?q=1",
+        ]
+    );
+}
+
+#[test]
+fn test_sort_by_lastmod() {
+    let lastmod = |day| Utc.with_ymd_and_hms(2023, 5, day, 0, 0, 0).unwrap();
+    let page = |path, lastmod: Option<chrono::DateTime<Utc>>| {
+        let page = Page::new(
+            Url::parse("https://example.com/")
+                .unwrap()
+                .join(path)
+                .unwrap(),
+        );
+        match lastmod {
+            Some(lastmod) => page.with_lastmod(lastmod),
+            None => page,
+        }
+    };
+    let mut sitemap = Sitemap {
+        pages: vec![
+            page("a", None),
+            page("b", Some(lastmod(2))),
+            page("c", Some(lastmod(9))),
+            page("d", None),
+            page("e", Some(lastmod(2))),
+            page("f", Some(lastmod(5))),
+        ],
+    };
+    let paths = |sitemap: &Sitemap| {
+        sitemap
+            .iter()
+            .map(|page| page.url.path().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    sitemap.sort_by_lastmod();
+    pretty_assertions::assert_eq!(paths(&sitemap), ["/c", "/f", "/b", "/e", "/a", "/d"]);
+
+    sitemap.sort_by_lastmod_asc();
+    pretty_assertions::assert_eq!(paths(&sitemap), ["/b", "/e", "/f", "/c", "/a", "/d"]);
+}
+
+#[test]
+fn test_rss() {
+    let lastmod = |day| Utc.with_ymd_and_hms(2023, 5, day, 8, 30, 0).unwrap();
+    let sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("https://example.com/").unwrap()).with_lastmod(lastmod(2)),
+            Page::new(Url::parse("https://example.com/draft").unwrap()),
+            Page::new(Url::parse("https://example.com/new").unwrap()).with_lastmod(lastmod(9)),
+            Page::new(Url::parse("https://example.com/old").unwrap()).with_lastmod(lastmod(1)),
+        ],
+    };
+    let options = RssOptions {
+        title: "Example".to_string(),
+        link: Url::parse("https://example.com/").unwrap(),
+        description: "Latest updates".to_string(),
+        max_items: 2,
+    };
+
+    pretty_assertions::assert_eq!(
+        sitemap.to_rss(&options).unwrap(),
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Example</title>
+    <link>https://example.com/</link>
+    <description>Latest updates</description>
+    <item>
+      <title>https://example.com/new</title>
+      <link>https://example.com/new</link>
+      <guid>https://example.com/new</guid>
+      <pubDate>Tue, 9 May 2023 08:30:00 +0000</pubDate>
+    </item>
+    <item>
+      <title>https://example.com/</title>
+      <link>https://example.com/</link>
+      <guid>https://example.com/</guid>
+      <pubDate>Tue, 2 May 2023 08:30:00 +0000</pubDate>
+    </item>
+  </channel>
+</rss>"#
+    );
+}
+
+#[test]
+fn test_stats() {
+    let lastmod = |day| {
+        Utc.with_ymd_and_hms(2023, 5, day, 0, 0, 0)
+            .unwrap()
+            .fixed_offset()
+    };
+    let mut sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("https://example.com/").unwrap())
+                .with_lastmod(lastmod(10))
+                .with_hash("34ef8d45ba04c3a5b98f1a25c0a6ac7b"),
+            Page::new(Url::parse("https://example.com/about").unwrap()).with_lastmod(lastmod(2)),
+            Page::new(Url::parse("https://blog.example.com/").unwrap()).with_lastmod(lastmod(21)),
+            Page::new(Url::parse("https://blog.example.com/post").unwrap()),
+        ],
+    };
+    sitemap.pages[0].content_length = Some(1024);
+    sitemap.pages[1].content_length = Some(512);
+
+    pretty_assertions::assert_eq!(
+        sitemap.stats(),
+        SitemapStats {
+            pages: 4,
+            with_lastmod: 3,
+            without_lastmod: 1,
+            with_hash: 1,
+            without_hash: 3,
+            oldest_lastmod: Some(lastmod(2)),
+            newest_lastmod: Some(lastmod(21)),
+            hosts: 2,
+            total_bytes: 1536,
+        }
+    );
+    pretty_assertions::assert_eq!(Sitemap { pages: vec![] }.stats(), SitemapStats::default());
+}
+
+#[test]
+fn test_retain() {
+    let page = |url: &str| Page::new(Url::parse(url).unwrap());
+    let mut sitemap = Sitemap {
+        pages: vec![
+            page("https://example.com/"),
+            page("https://example.com/tag/rust"),
+            page("https://blog.example.com/"),
+        ],
+    };
+
+    sitemap.retain(|page| !page.url.path().starts_with("/tag/"));
+    pretty_assertions::assert_eq!(
+        sitemap.pages,
+        [
+            page("https://example.com/"),
+            page("https://blog.example.com/")
+        ]
+    );
+
+    sitemap.filter_by_host("blog.example.com");
+    pretty_assertions::assert_eq!(sitemap.pages, [page("https://blog.example.com/")]);
+}
+
+#[test]
+fn test_merge() {
+    let page = |path: &str, lastmod: i64| {
+        Page::new(
+            Url::parse("https://example.com/")
+                .unwrap()
+                .join(path)
+                .unwrap(),
+        )
+        .with_lastmod(Utc.timestamp_opt(lastmod, 0).unwrap())
+    };
+    let docs = Sitemap {
+        pages: vec![page("/docs", 100), page("/shared", 100)],
+    };
+    let blog = Sitemap {
+        pages: vec![page("/shared", 200), page("/blog", 100)],
+    };
+    let expected = [page("/docs", 100), page("/shared", 200), page("/blog", 100)];
+
+    let mut merged = docs.clone();
+    merged.merge(blog.clone());
+    pretty_assertions::assert_eq!(merged.pages, expected);
+
+    let merged = Sitemap::from_iter([docs, blog]);
+    pretty_assertions::assert_eq!(merged.pages, expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json() {
+    let sitemap = Sitemap::deserialize(include_str!("data/simple-sitemap.xml").as_bytes()).unwrap();
+
+    let json = sitemap.to_json().unwrap();
+    pretty_assertions::assert_eq!(Sitemap::from_json(&json).unwrap(), sitemap);
+
+    let sitemap = Sitemap::from_json(
+        r#"{"pages": [{"url": "https://example.com/", "lastmod": "2023-08-13T11:30:46+02:00", "hash": null}]}"#,
+    )
+    .unwrap();
+    pretty_assertions::assert_eq!(
+        sitemap.pages,
+        [
+            Page::new(Url::parse("https://example.com/").unwrap()).with_lastmod(
+                chrono::DateTime::parse_from_rfc3339("2023-08-13T11:30:46+02:00").unwrap()
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_text() {
+    let mut sitemap =
+        Sitemap::deserialize(include_str!("data/simple-sitemap.xml").as_bytes()).unwrap();
+
+    let mut buf = vec![];
+    sitemap.serialize_text(&mut buf).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+    pretty_assertions::assert_eq!(text.lines().count(), sitemap.len());
+    pretty_assertions::assert_eq!(text.lines().next(), Some(sitemap.pages[0].url.as_str()));
+
+    for page in sitemap.iter_mut() {
+        *page = Page::new(page.url.clone());
+    }
+    pretty_assertions::assert_eq!(Sitemap::deserialize_text(text.as_bytes()).unwrap(), sitemap);
+
+    let error =
+        Sitemap::deserialize_text("https://example.com/\n\nnot a url\n".as_bytes()).unwrap_err();
+    assert!(error.contains("line 3"), "{}", error);
+}
+
+#[test]
+fn test_csv() {
+    let lastmod = chrono::DateTime::parse_from_rfc3339("2023-08-13T11:30:46.5+02:00").unwrap();
+    let mut sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("https://example.com/").unwrap())
+                .with_lastmod(lastmod)
+                .with_hash("34ef8d45ba04c3a5b98f1a25c0a6ac7b"),
+            Page::new(Url::parse("https://example.com/a,b").unwrap()).with_lastmod(lastmod),
+            Page::new(Url::parse("https://example.com/c").unwrap())
+                .with_hash("1f0e8893210f6496401d171ff77c7e92"),
+            Page::new(Url::parse("https://example.com/d").unwrap()),
+        ],
+    };
+    sitemap.pages[0].content_length = Some(1234);
+
+    let mut buf = vec![];
+    sitemap.to_csv(&mut buf).unwrap();
+    let csv = String::from_utf8(buf).unwrap();
+    pretty_assertions::assert_eq!(
+        csv.lines().take(3).collect::<Vec<_>>(),
+        [
+            "url,lastmod,hash,content_length",
+            "https://example.com/,2023-08-13T11:30:46.500+02:00,34ef8d45ba04c3a5b98f1a25c0a6ac7b,1234",
+            r#""https://example.com/a,b",2023-08-13T11:30:46.500+02:00,,"#,
+        ]
+    );
+    pretty_assertions::assert_eq!(Sitemap::from_csv(csv.as_bytes()).unwrap(), sitemap);
+
+    let csv = "https://example.com/,,,extra,columns\nhttps://example.com/d\n";
+    pretty_assertions::assert_eq!(
+        Sitemap::from_csv(csv.as_bytes()).unwrap().pages,
+        [
+            Page::new(Url::parse("https://example.com/").unwrap()),
+            Page::new(Url::parse("https://example.com/d").unwrap()),
+        ]
+    );
+
+    let csv = "url,lastmod,hash,note\nhttps://example.com/,,,extra\n";
+    pretty_assertions::assert_eq!(
+        Sitemap::from_csv(csv.as_bytes()).unwrap().pages,
+        [Page::new(Url::parse("https://example.com/").unwrap())]
+    );
+}
+
+#[test]
+fn test_hash_mode() {
+    let html = "<html>\n  <body>\n    <p>Hello,   world!</p>\n  </body>\n</html>\n";
+    let reformatted = "<html>\r\n<body>\r\n\t<p>Hello, world!</p>\r\n</body>\r\n</html>";
+
+    assert_ne!(
+        HashMode::Raw.fingerprint(html),
+        HashMode::Raw.fingerprint(reformatted)
+    );
+    pretty_assertions::assert_eq!(
+        HashMode::NormalizeWhitespace.fingerprint(html),
+        HashMode::NormalizeWhitespace.fingerprint(reformatted)
+    );
+    pretty_assertions::assert_eq!(
+        HashMode::Raw.fingerprint(" a\r\nb\n"),
+        format!("{:x}", md5::compute("a\nb"))
+    );
+}
+
+#[test]
+fn test_hash_strip_scripts() {
+    let html = |nonce: &str| {
+        format!(
+            r#"<html><head><SCRIPT nonce="{nonce}">track("{nonce}");</SCRIPT><style>.a{{}}</style></head><body><!-- {nonce} --><p>Hi</p><scripts></scripts></body></html>"#
+        )
+    };
+    let mode = HashMode::StripScripts {
+        strip_comments: false,
+    };
+    pretty_assertions::assert_eq!(
+        mode.fingerprint(&html("abc")),
+        HashMode::Raw.fingerprint(
+            "<html><head></head><body><!-- abc --><p>Hi</p><scripts></scripts></body></html>"
+        )
+    );
+    assert_ne!(
+        mode.fingerprint(&html("abc")),
+        mode.fingerprint(&html("xyz"))
+    );
+
+    let mode = HashMode::StripScripts {
+        strip_comments: true,
+    };
+    pretty_assertions::assert_eq!(
+        mode.fingerprint(&html("abc")),
+        mode.fingerprint(&html("xyz"))
+    );
+    assert_ne!(
+        HashMode::Raw.fingerprint(&html("abc")),
+        HashMode::Raw.fingerprint(&html("xyz"))
+    );
+}
+
+#[test]
+fn test_files() {
+    let sitemap = Sitemap::deserialize(include_str!("data/simple-sitemap.xml").as_bytes()).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    for filename in ["sitemap.xml", "sitemap.xml.gz"] {
+        let path = dir.path().join(filename);
+        sitemap.write_to_file(&path).unwrap();
+        pretty_assertions::assert_eq!(Sitemap::read_from_file(&path).unwrap(), sitemap);
+    }
+    let compressed = std::fs::read(dir.path().join("sitemap.xml.gz")).unwrap();
+    pretty_assertions::assert_eq!(&compressed[..2], [0x1f, 0x8b]);
+
+    let missing = dir.path().join("missing.xml");
+    let error = Sitemap::read_from_file(&missing).unwrap_err();
+    assert!(error.contains(&missing.display().to_string()));
+}
+
+#[test]
+fn test_xml_string() {
+    let original = include_str!("data/simple-sitemap-trimmed.xml")
+        .trim()
+        .replace("\r\n", "\n");
+    let sitemap = Sitemap::from_xml_str(&original).unwrap();
+
+    let mut buf = vec![];
+    sitemap.serialize(&mut buf).unwrap();
+    pretty_assertions::assert_eq!(sitemap.to_xml_string().unwrap().as_bytes(), buf);
+    pretty_assertions::assert_eq!(sitemap.to_xml_string().unwrap(), original);
+}
+
+#[test]
+fn test_url_limit() {
+    let page = |i: usize| Page::new(Url::parse(&format!("https://example.com/{i}")).unwrap());
+
+    let mut sitemap = Sitemap {
+        pages: (0..MAX_URLS).map(page).collect(),
+    };
+    pretty_assertions::assert_eq!(sitemap.validate(), Ok(()));
+
+    sitemap.pages.push(page(MAX_URLS));
+    pretty_assertions::assert_eq!(
+        sitemap.validate(),
+        Err(vec![ValidationError::TooManyUrls {
+            count: MAX_URLS + 1
+        }])
+    );
+    assert!(sitemap.serialize(std::io::sink()).is_err());
+}
+
+#[test]
+fn test_validate_schema() {
+    let long_url = Url::parse(&format!(
+        "https://example.com/{}",
+        "a".repeat(MAX_URL_LENGTH - 20)
+    ))
+    .unwrap();
+    let old_url = Url::parse("https://example.com/old").unwrap();
+    let sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("https://example.com/").unwrap())
+                .with_lastmod(Utc.timestamp_opt(61, 0).unwrap()),
+            Page::new(long_url.clone()),
+            Page::new(old_url.clone())
+                .with_lastmod(Utc.with_ymd_and_hms(-1, 1, 1, 0, 0, 0).unwrap()),
+        ],
+    };
+
+    pretty_assertions::assert_eq!(long_url.as_str().len(), MAX_URL_LENGTH);
+    pretty_assertions::assert_eq!(sitemap.validate(), Ok(()));
+    pretty_assertions::assert_eq!(
+        sitemap.validate_schema(),
+        Err(vec![ValidationError::InvalidLastmod {
+            url: old_url.clone()
+        }])
+    );
+
+    let too_long_url = Url::parse(&format!("{}a", long_url)).unwrap();
+    let mut sitemap = sitemap;
+    sitemap.pages.push(Page::new(too_long_url.clone()));
+    let too_long = ValidationError::UrlTooLong {
+        url: too_long_url.clone(),
+        length: MAX_URL_LENGTH + 1,
+    };
+    pretty_assertions::assert_eq!(sitemap.validate(), Err(vec![too_long.clone()]));
+    pretty_assertions::assert_eq!(
+        sitemap.validate_schema(),
+        Err(vec![
+            too_long.clone(),
+            ValidationError::InvalidLastmod { url: old_url }
+        ])
+    );
+    assert!(too_long.to_string().contains(too_long_url.as_str()));
+}
+
+#[test]
+fn test_validate_priority() {
+    let url = Url::parse("https://example.com/").unwrap();
+    let mut sitemap = Sitemap {
+        pages: vec![
+            Page::new(url.clone()).with_priority(0.0),
+            Page::new(url.join("/a").unwrap()).with_priority(1.0),
+        ],
+    };
+    pretty_assertions::assert_eq!(sitemap.validate_schema(), Ok(()));
+
+    sitemap
+        .pages
+        .push(Page::new(url.join("/b").unwrap()).with_priority(3.5));
+    sitemap
+        .pages
+        .push(Page::new(url.join("/c").unwrap()).with_priority(-0.1));
+    pretty_assertions::assert_eq!(sitemap.validate(), Ok(()));
+    pretty_assertions::assert_eq!(
+        sitemap.validate_schema(),
+        Err(vec![
+            ValidationError::InvalidPriority {
+                url: url.join("/b").unwrap(),
+                priority: 3.5,
+            },
+            ValidationError::InvalidPriority {
+                url: url.join("/c").unwrap(),
+                priority: -0.1,
+            },
+        ])
+    );
+
+    sitemap.pages.truncate(2);
+    sitemap
+        .pages
+        .push(Page::new(url.join("/nan").unwrap()).with_priority(f32::NAN));
+    let errors = sitemap.validate_schema().unwrap_err();
+    assert!(matches!(
+        &errors[..],
+        [ValidationError::InvalidPriority { priority, .. }] if priority.is_nan()
+    ));
+    assert!(errors[0].to_string().contains("NaN"));
+}
+
+#[test]
+fn test_mixed_hosts() {
+    let url = |url: &str| Url::parse(url).unwrap();
+    let mut sitemap = Sitemap {
+        pages: vec![
+            Page::new(url("https://example.com/")),
+            Page::new(url("https://www.example.com/about")),
+            Page::new(url("https://example.com/blog")),
+            Page::new(url("https://other.com/")),
+        ],
+    };
+
+    pretty_assertions::assert_eq!(sitemap.validate(), Ok(()));
+    let warnings = sitemap.warnings();
+    pretty_assertions::assert_eq!(
+        warnings,
+        [ValidationError::MixedHosts {
+            host: "example.com".to_string(),
+            urls: vec![
+                url("https://www.example.com/about"),
+                url("https://other.com/")
+            ],
+        }]
+    );
+    assert!(warnings[0].to_string().contains("https://other.com/"));
+
+    sitemap.filter_by_host("example.com");
+    assert!(sitemap.warnings().is_empty());
+}
+
+#[test]
+fn test_robots_txt() {
+    let url = Url::parse("https://example.com/sitemap.xml").unwrap();
+    pretty_assertions::assert_eq!(
+        Sitemap::robots_txt_line(&url),
+        "Sitemap: https://example.com/sitemap.xml"
+    );
+    pretty_assertions::assert_eq!(
+        SitemapIndex::robots_txt_line(&url),
+        Sitemap::robots_txt_line(&url)
+    );
+
+    let robots_txt = "User-agent: *\nDisallow: /private";
+    let block = Sitemap::robots_txt_block(robots_txt, &url);
+    pretty_assertions::assert_eq!(
+        block,
+        "User-agent: *\nDisallow: /private\nSitemap: https://example.com/sitemap.xml\n"
+    );
+    pretty_assertions::assert_eq!(Sitemap::robots_txt_block(&block, &url), block);
+    pretty_assertions::assert_eq!(
+        Sitemap::robots_txt_block("", &url),
+        "Sitemap: https://example.com/sitemap.xml\n"
+    );
+}
+
+#[test]
+fn test_serialize_streaming() {
+    let mut sitemap =
+        Sitemap::deserialize(include_str!("data/simple-sitemap.xml").as_bytes()).unwrap();
+    sitemap.pages.push(Page {
+        images: vec![ImageEntry {
+            loc: Url::parse("https://example.com/cat.jpg").unwrap(),
+            title: None,
+            caption: None,
+        }],
+        videos: vec![VideoEntry {
+            thumbnail_loc: None,
+            title: "Cat".into(),
+            description: "A cat on a mat".into(),
+            content_loc: Some(Url::parse("https://example.com/cat.mp4").unwrap()),
+            player_loc: None,
+            duration: None,
+        }],
+        news: Some(NewsEntry {
+            publication_name: "Example Times".into(),
+            publication_language: "en".into(),
+            publication_date: Utc.timestamp_opt(61, 0).unwrap(),
+            title: "Cat found".into(),
+        }),
+        ..Page::new(Url::parse("https://example.com/gallery").unwrap())
+    });
+
+    let mut streamed = vec![];
+    Sitemap::serialize_streaming(sitemap.clone().into_iter(), &mut streamed).unwrap();
+    pretty_assertions::assert_eq!(
+        String::from_utf8(streamed).unwrap(),
+        sitemap.to_xml_string().unwrap()
+    );
+
+    // The pages are not known in advance, so every extension namespace is declared.
+    let mut streamed = vec![];
+    Sitemap::serialize_streaming(std::iter::empty(), &mut streamed).unwrap();
+    let streamed = String::from_utf8(streamed).unwrap();
+    assert!(streamed.contains("xmlns:news="));
+    pretty_assertions::assert_eq!(
+        Sitemap::deserialize(streamed.as_bytes()).unwrap(),
+        Sitemap { pages: vec![] }
+    );
+}
+
+#[test]
+fn test_serialize_split() {
+    let sitemap = Sitemap {
+        pages: (0..500)
+            .map(|i| {
+                Page::new(
+                    Url::parse(&format!("https://example.com/some/fairly/long/path/{i}")).unwrap(),
+                )
+                .with_lastmod(Utc.timestamp_opt(i, 0).unwrap())
+                .with_hash("0123456789abcdef0123456789abcdef")
+            })
+            .collect(),
+    };
+    let base_url = Url::parse("https://example.com/").unwrap();
+
+    let max_bytes = 20_000;
+    let split = sitemap
+        .serialize_split(MAX_URLS, max_bytes, &base_url)
+        .unwrap();
+    more_asserts::assert_gt!(split.documents.len(), 1);
+    pretty_assertions::assert_eq!(split.index.sitemaps.len(), split.documents.len());
+
+    let mut pages = vec![];
+    for (document, entry) in split.documents.iter().zip(&split.index.sitemaps) {
+        more_asserts::assert_le!(document.len(), max_bytes);
+        let chunk = Sitemap::deserialize(&document[..]).unwrap();
+        pretty_assertions::assert_eq!(
+            entry.lastmod,
+            chunk.pages.iter().filter_map(|page| page.lastmod).max()
+        );
+        pages.extend(chunk.pages);
+    }
+    pretty_assertions::assert_eq!(pages, sitemap.pages);
+    pretty_assertions::assert_eq!(
+        split.index.sitemaps[1].loc,
+        base_url.join("sitemap-2.xml").unwrap()
+    );
+
+    let mut buf = vec![];
+    split.index.serialize(&mut buf).unwrap();
+    pretty_assertions::assert_eq!(SitemapIndex::deserialize(&buf[..]).unwrap(), split.index);
+
+    let split = sitemap.serialize_split(100, usize::MAX, &base_url).unwrap();
+    pretty_assertions::assert_eq!(split.documents.len(), 5);
+}
+
+#[test]
+fn test_as_index_entry() {
+    let loc = Url::parse("https://example.com/sitemap-1.xml").unwrap();
+    let lastmod = |hour| {
+        Utc.with_ymd_and_hms(2023, 5, 10, hour, 0, 0)
+            .unwrap()
+            .fixed_offset()
+    };
+    let sitemap = Sitemap {
+        pages: vec![
+            Page::new(Url::parse("https://example.com/").unwrap()).with_lastmod(lastmod(3)),
+            Page::new(Url::parse("https://example.com/a").unwrap()),
+            // Earlier than the first page, despite the later local time.
+            Page::new(Url::parse("https://example.com/b").unwrap()).with_lastmod(
+                lastmod(2).with_timezone(&chrono::FixedOffset::east_opt(5 * 3600).unwrap()),
+            ),
+        ],
+    };
+    pretty_assertions::assert_eq!(
+        sitemap.as_index_entry(loc.clone()),
+        SitemapIndexEntry {
+            loc: loc.clone(),
+            lastmod: Some(lastmod(3)),
+        }
+    );
+
+    let sitemap = Sitemap {
+        pages: vec![Page::new(Url::parse("https://example.com/").unwrap())],
+    };
+    pretty_assertions::assert_eq!(sitemap.as_index_entry(loc).lastmod, None);
+}
+
+#[test]
+fn test_deserialize_any() {
+    let sitemap = include_str!("data/simple-sitemap.xml");
+    pretty_assertions::assert_eq!(
+        Sitemap::deserialize_any(sitemap.as_bytes()).unwrap(),
+        SitemapOrIndex::Sitemap(Sitemap::from_xml_str(sitemap).unwrap())
+    );
+
+    let index = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- generated by some tool -->
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap>
+    <loc>https://example.com/sitemap-1.xml</loc>
+    <lastmod>2023-08-13T11:30:46Z</lastmod>
+  </sitemap>
+  <sitemap>
+    <loc>https://example.com/sitemap-2.xml</loc>
+  </sitemap>
+</sitemapindex>"#;
+    pretty_assertions::assert_eq!(
+        Sitemap::deserialize_any(index.as_bytes()).unwrap(),
+        SitemapOrIndex::Index(SitemapIndex {
+            sitemaps: vec![
+                SitemapIndexEntry {
+                    loc: Url::parse("https://example.com/sitemap-1.xml").unwrap(),
+                    lastmod: Some(
+                        Utc.with_ymd_and_hms(2023, 8, 13, 11, 30, 46)
+                            .unwrap()
+                            .fixed_offset()
+                    ),
+                },
+                SitemapIndexEntry {
+                    loc: Url::parse("https://example.com/sitemap-2.xml").unwrap(),
+                    lastmod: None,
+                },
+            ],
+        })
+    );
+
+    let error = Sitemap::deserialize_any(&b"<rss></rss>"[..]).unwrap_err();
+    pretty_assertions::assert_eq!(
+        error,
+        "failed to deserialize: unexpected root element <rss>"
+    );
+    assert!(Sitemap::deserialize_any(&b""[..]).is_err());
+}
+
+mod sitemap {
+    use super::*;
+    use axum::response::Html;
+    use axum::{routing::get, Router};
+    use std::net::SocketAddr;
+
+    #[tokio::test]
+    async fn test_generation_and_update() {
+        let (mut new_sitemap, mut old_sitemap) = obtain_sitemaps().await.unwrap();
+
+        let correct_urls = [
+            Url::parse("http://localhost:3000/").unwrap(),
+            Url::parse("http://localhost:3000/a").unwrap(),
+            Url::parse("http://localhost:3000/b").unwrap(),
+            Url::parse("http://localhost:3000/c").unwrap(),
+            // Shouldn't be reachable by crawling:
+            // Url::parse("http://localhost:3000/d").unwrap(),
+        ];
+
+        for (page, correct_url) in new_sitemap.pages.iter().zip(correct_urls.iter()) {
+            pretty_assertions::assert_eq!(page.url, correct_url.clone());
+        }
+
+        old_sitemap.update_domain("http://localhost:3000").unwrap();
+        let diff = new_sitemap.diff(&old_sitemap);
+        let info = new_sitemap.combine_with_old_sitemap(&old_sitemap).unwrap();
+        new_sitemap.update_domain("https://example.com").unwrap();
+
+        let correct_urls = [
+            Url::parse("https://example.com/").unwrap(),
+            Url::parse("https://example.com/a").unwrap(),
+            Url::parse("https://example.com/b").unwrap(),
+            Url::parse("https://example.com/c").unwrap(),
+            // Shouldn't be reachable by crawling:
+            // Url::parse("http://localhost:3000/d").unwrap(),
+        ];
+        let updated_urls = [
+            Url::parse("https://example.com/a").unwrap(),
+            Url::parse("https://example.com/b").unwrap(),
+            Url::parse("https://example.com/c").unwrap(),
+        ];
+        for (page, correct_url) in new_sitemap.pages.iter().zip(correct_urls.iter()) {
+            pretty_assertions::assert_eq!(page.url, correct_url.clone());
+            let lastmod = page.lastmod.unwrap();
+
+            if updated_urls.contains(&page.url) {
+                pretty_assertions::assert_eq!(lastmod, crawl_time());
+            } else {
+                pretty_assertions::assert_eq!(
+                    lastmod,
+                    Utc.with_ymd_and_hms(2020, 1, 5, 0, 0, 0).unwrap()
+                );
+            }
+        }
+
+        let correct_info = UpdateInfo {
+            new_pages: vec![Url::parse("http://localhost:3000/c").unwrap()],
+            removed_pages: vec![
+                Url::parse("http://localhost:3000/old-nonexistent-page-with-hash").unwrap(),
+                Url::parse("http://localhost:3000/old-nonexistent-page-without-hash").unwrap(),
+            ],
+            unchanged_pages: vec![Url::parse("http://localhost:3000/").unwrap()],
+            updated_pages: vec![
+                Url::parse("http://localhost:3000/a").unwrap(),
+                Url::parse("http://localhost:3000/b").unwrap(),
+            ],
+            kept_pages: vec![],
+        };
+        pretty_assertions::assert_eq!(info, correct_info);
+        pretty_assertions::assert_eq!(diff, correct_info);
+        pretty_assertions::assert_eq!(info.summary(), "1 new, 2 removed, 2 updated, 1 unchanged");
+        assert!(info.has_changes());
+    }
+
+    fn crawl_time() -> chrono::DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 8, 13, 11, 30, 46).unwrap()
+    }
+
+    async fn obtain_sitemaps() -> Result<(Sitemap, Sitemap), String> {
+        let app = Router::new()
+            .route("/", get(root))
+            .route("/a", get(a))
+            .route("/b", get(b))
+            .route("/c", get(c))
+            .route("/d", get(d))
+            .route("/sitemap.xml", get(sitemap));
+
+        let port = 3000;
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        let server = axum::Server::bind(&addr).serve(app.into_make_service());
+
+        // Prepare some signal for when the server should start shutting down...
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let graceful = server.with_graceful_shutdown(async {
+            rx.await.ok();
+        });
+
+        println!("Listening on http://localhost:{port}");
+        tokio::spawn(async {
+            if let Err(e) = graceful.await {
+                eprintln!("server error: {}", e);
+            }
+        });
+
+        let config = CrawlConfig {
+            crawl_time: Some(crawl_time()),
+            ..Default::default()
+        };
+        let mut new_sitemap =
+            Sitemap::generate_by_crawling_with_config("http://localhost:3000", &config)
+                .await?
+                .sitemap;
+        new_sitemap.sort_by_url();
+
+        // Try with `url::Url` instead of `&str`:
+        let old_sitemap =
+            Sitemap::import(Url::parse("http://localhost:3000/sitemap.xml").unwrap()).await?;
+
+        // Shut down the server...
+        let _ = tx.send(());
+
+        Ok((new_sitemap, old_sitemap))
+    }
+
+    // Reachable from / and /c.
+    // Also ensure it works on all OSs by importing a file, which may have \n or \r\n line endings.
+    async fn root() -> Html<&'static str> {
+        Html(include_str!("data/root.html"))
+    }
+
+    // Reachable from /.
+    async fn a() -> Html<&'static str> {
+        Html(
+            r#"
+<html>
+  <body>
+    <a href="/b">Reachable from home and a</a>
+    <a href="/c">Reachable from home a</a>
+  </body>
+</html>
+"#,
+        )
+    }
+
+    // Reachable from /home and /a.
+    async fn b() -> Html<&'static str> {
+        Html(
+            r#"
+<html>
+  <body>
+  </body>
+</html>
+"#,
+        )
+    }
+
+    // Reachable from /a and /c.
+    async fn c() -> Html<&'static str> {
+        Html(
+            r#"
+<html>
+  <body>
+    <a href="/c">Reachable from itself</a>
+    <a href="/root">Reachable from home and c</a>
+  </body>
+</html>
+"#,
+        )
+    }
+
+    // Unreachable.
+    async fn d() -> Html<&'static str> {
+        Html(
+            r#"
+            <html><body>
+                <h1>Unreachable!</h1>
+            </body></html>
+        "#,
+        )
+    }
+
+    // Sitemap.
+    async fn sitemap() -> &'static str {
+        include_str!("data/old-sitemap.xml")
+    }
+}
+
+mod crawl {
+    use super::*;
+    use axum::response::Html;
+    use axum::{routing::get, Router};
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serves `app` on a free local port and returns its base URL and a shutdown handle.
+    pub(crate) async fn serve(app: Router) -> (Url, tokio::sync::oneshot::Sender<()>) {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+        let server = axum::Server::bind(&addr).serve(app.into_make_service());
+        let url = Url::parse(&format!("http://{}/", server.local_addr())).unwrap();
+
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let graceful = server.with_graceful_shutdown(async {
+            rx.await.ok();
+        });
+        tokio::spawn(async {
+            if let Err(e) = graceful.await {
+                eprintln!("server error: {}", e);
+            }
+        });
+
+        (url, tx)
+    }
+
+    /// Returns the URLs of the sitemap in sorted order.
+    fn sorted_urls(mut sitemap: Sitemap) -> Vec<Url> {
+        sitemap.sort_by_url();
+        sitemap.pages.into_iter().map(|page| page.url).collect()
+    }
+
+    static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+    static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+    async fn slow_page() -> Html<&'static str> {
+        let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+        MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+        Html(r#"<html><body><a href="/1">1</a><a href="/2">2</a><a href="/3">3</a></body></html>"#)
+    }
+
+    #[tokio::test]
+    async fn test_sequential_crawl() {
+        let app = Router::new()
+            .route("/", get(slow_page))
+            .route("/1", get(slow_page))
+            .route("/2", get(slow_page))
+            .route("/3", get(slow_page));
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            concurrency: 1,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url, &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(sitemap.pages.len(), 4);
+        pretty_assertions::assert_eq!(MAX_IN_FLIGHT.load(Ordering::SeqCst), 1);
+    }
+
+    async fn hanging_page() -> Html<&'static str> {
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        Html("<html><body></body></html>")
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/fast">Fast</a><a href="/slow">Slow</a></body></html>"#)
+                }),
+            )
+            .route("/fast", get(|| async { Html("<html><body></body></html>") }))
+            .route("/slow", get(hanging_page));
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            request_timeout: std::time::Duration::from_millis(200),
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/fast").unwrap()]
+        );
+        pretty_assertions::assert_eq!(report.timed_out, [url.join("/slow").unwrap()]);
+    }
+
+    static USER_AGENT: std::sync::Mutex<String> = std::sync::Mutex::new(String::new());
+
+    #[tokio::test]
+    async fn test_user_agent() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|headers: axum::http::HeaderMap| async move {
+                    *USER_AGENT.lock().unwrap() = headers["user-agent"].to_str().unwrap().into();
+                    Html(r#"<html><body><a href="/public">Public</a><a href="/private">Private</a></body></html>"#)
+                }),
+            )
+            .route("/public", get(|| async { Html("<html><body></body></html>") }))
+            .route("/private", get(|| async { Html("<html><body></body></html>") }))
+            .route(
+                "/robots.txt",
+                get(|| async { "User-agent: my-bot\nDisallow: /private\n" }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            user_agent: Some("my-bot/1.0".into()),
+            respect_robots_txt: true,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(
+            sorted_urls(sitemap),
+            [url.clone(), url.join("/public").unwrap()]
+        );
+        pretty_assertions::assert_eq!(*USER_AGENT.lock().unwrap(), "my-bot/1.0");
+    }
+
+    #[tokio::test]
+    async fn test_broken_robots_txt() {
+        use std::io::{BufRead, Write};
+
+        // `robots.txt` starts fine but stalls mid-body until the request times out.
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                std::thread::spawn(move || {
+                    let mut request_line = String::new();
+                    let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap() > 2 {
+                        line.clear();
+                    }
+
+                    if request_line.starts_with("GET /robots.txt ") {
+                        let _ = stream.write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\nUser-agent: *\n",
+                        );
+                        std::thread::sleep(std::time::Duration::from_secs(2));
+                    } else {
+                        let body = "<html><body></body></html>";
+                        let _ = write!(
+                            stream,
+                            "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                    }
+                });
+            }
+        });
+
+        let config = CrawlConfig {
+            request_timeout: std::time::Duration::from_millis(300),
+            respect_robots_txt: true,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(sorted_urls(sitemap), [url]);
+    }
+
+    #[tokio::test]
+    async fn test_delay_between_requests() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/a">A</a><a href="/b">B</a></body></html>"#)
+                }),
+            )
+            .route("/a", get(|| async { Html("<html><body></body></html>") }))
+            .route("/b", get(|| async { Html("<html><body></body></html>") }))
+            .route(
+                "/robots.txt",
+                get(|| async { "User-agent: *\nCrawl-delay: 0.15\n" }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            delay_between_requests: std::time::Duration::from_millis(50),
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        let sitemap = Sitemap::generate_by_crawling_with_config(url, &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let elapsed = start.elapsed();
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(sitemap.pages.len(), 3);
+        // `Crawl-delay` is larger than the configured delay, so it wins.
+        more_asserts::assert_ge!(elapsed, std::time::Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_subdomains() {
+        use axum::response::IntoResponse;
+
+        // Requests go through a proxy, so that any host name reaches the test server.
+        let proxy = Router::new().fallback(|uri: axum::http::Uri| async move {
+            match uri.to_string().as_str() {
+                "http://www.example.co.uk/" => Html(
+                    r#"<html><body>
+                        <a href="/page">Same host</a>
+                        <a href="http://blog.example.co.uk/">Subdomain</a>
+                        <a href="http://other.co.uk/">Same public suffix</a>
+                        <a href="http://example.com/">Third party</a>
+                    </body></html>"#,
+                )
+                .into_response(),
+                "http://www.example.co.uk/page" | "http://blog.example.co.uk/" => {
+                    Html("<html><body></body></html>").into_response()
+                }
+                _ => axum::http::StatusCode::NOT_FOUND.into_response(),
+            }
+        });
+        let (proxy_url, tx) = serve(proxy).await;
+        let url = Url::parse("http://www.example.co.uk/").unwrap();
+
+        for crawl_subdomains in [false, true] {
+            let config = CrawlConfig {
+                crawl_subdomains,
+                proxy: Some(proxy_url.clone()),
+                ..Default::default()
+            };
+            let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+                .await
+                .unwrap();
+
+            let mut expected = vec![url.clone(), url.join("/page").unwrap()];
+            let mut external = vec![
+                Url::parse("http://example.com/").unwrap(),
+                Url::parse("http://other.co.uk/").unwrap(),
+            ];
+            let subdomain = Url::parse("http://blog.example.co.uk/").unwrap();
+            if crawl_subdomains {
+                expected.insert(0, subdomain);
+            } else {
+                external.insert(0, subdomain);
+            }
+            pretty_assertions::assert_eq!(sorted_urls(report.sitemap), expected);
+            let mut skipped = report
+                .skipped
+                .into_iter()
+                .filter(|(_, reason)| *reason == SkipReason::External)
+                .map(|(url, _)| url)
+                .collect::<Vec<_>>();
+            skipped.sort();
+            pretty_assertions::assert_eq!(skipped, external);
+        }
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_last_modified_header() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    (
+                        [("last-modified", "Sun, 06 Nov 1994 08:49:37 GMT")],
+                        Html(r#"<html><body><a href="/fresh">Fresh</a></body></html>"#),
+                    )
+                }),
+            )
+            .route(
+                "/fresh",
+                get(|| async { Html("<html><body></body></html>") }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let lastmod = |sitemap: &Sitemap, path: &str| {
+            let url = url.join(path).unwrap();
+            sitemap
+                .pages
+                .iter()
+                .find(|page| page.url == url)
+                .unwrap()
+                .lastmod
+                .unwrap()
+        };
+
+        let start = Utc::now();
+        let config = CrawlConfig {
+            lastmod_policy: LastmodPolicy::HttpHeader,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(
+            lastmod(&sitemap, "/"),
+            Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap()
+        );
+        more_asserts::assert_ge!(lastmod(&sitemap, "/fresh"), start);
+
+        let sitemap = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        more_asserts::assert_ge!(lastmod(&sitemap, "/"), start);
+
+        let config = CrawlConfig {
+            lastmod_policy: LastmodPolicy::HttpHeader,
+            omit_lastmod: true,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
+        pretty_assertions::assert_eq!(sitemap.pages.len(), 2);
+        assert!(sitemap.pages.iter().all(|page| page.lastmod.is_none()));
+        assert!(!sitemap.to_xml_string().unwrap().contains("<lastmod>"));
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_gzip() {
+        let compressed = gzip(include_bytes!("data/simple-sitemap.xml"));
+        let truncated = compressed[..compressed.len() / 2].to_vec();
+        let app = Router::new()
+            .route(
+                "/sitemap.xml.gz",
+                get({
+                    let compressed = compressed.clone();
+                    || async move { compressed }
+                }),
+            )
+            .route(
+                "/sitemap.xml",
+                get(|| async move { ([("content-encoding", "gzip")], compressed) }),
+            )
+            .route("/truncated.xml.gz", get(|| async move { truncated }));
+        let (url, tx) = serve(app).await;
+
+        let expected =
+            Sitemap::deserialize(include_str!("data/simple-sitemap.xml").as_bytes()).unwrap();
+        for path in ["sitemap.xml.gz", "sitemap.xml"] {
+            let sitemap = Sitemap::import(url.join(path).unwrap()).await.unwrap();
+            pretty_assertions::assert_eq!(sitemap, expected);
+        }
+
+        let error = Sitemap::import(url.join("truncated.xml.gz").unwrap())
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("failed to decompress"));
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_redirects() {
+        use axum::response::Redirect;
+
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/old">Old</a><a href="/older">Older</a><a href="/new">New</a></body></html>"#)
+                }),
+            )
+            .route("/older", get(|| async { Redirect::permanent("/old") }))
+            .route("/old", get(|| async { Redirect::permanent("/new") }))
+            .route("/new", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let report =
+            Sitemap::generate_by_crawling_with_config(url.clone(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/new").unwrap()]
+        );
+        pretty_assertions::assert_eq!(
+            report.redirects,
+            [
+                (url.join("/old").unwrap(), url.join("/new").unwrap()),
+                (url.join("/older").unwrap(), url.join("/new").unwrap()),
+            ]
+        );
+
+        let config = CrawlConfig {
+            max_redirects: 1,
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        pretty_assertions::assert_eq!(
+            report.redirects,
+            [(url.join("/old").unwrap(), url.join("/new").unwrap())]
+        );
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_error_pages() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/missing">Missing</a><a href="/page">Page</a></body></html>"#)
+                }),
+            )
+            .route("/page", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+        let missing = url.join("/missing").unwrap();
+
+        let report =
+            Sitemap::generate_by_crawling_with_config(url.clone(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/page").unwrap()]
+        );
+        pretty_assertions::assert_eq!(report.error_pages, [(missing.clone(), 404)]);
+
+        let config = CrawlConfig {
+            include_error_pages: true,
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        assert!(report.sitemap.contains(&missing));
+        pretty_assertions::assert_eq!(report.error_pages, [(missing, 404)]);
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_query_strings() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/list?page=2">Page 2</a><a href="/list?sort=asc">Sorted</a></body></html>"#)
+                }),
+            )
+            .route("/list", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            exclude_query_strings: true,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(sorted_urls(sitemap), vec![url.clone()]);
+
+        let config = CrawlConfig {
+            strip_query_params: vec!["sort".into()],
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(
+            sorted_urls(sitemap),
+            [
+                url.clone(),
+                url.join("/list").unwrap(),
+                url.join("/list?page=2").unwrap()
+            ]
+        );
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_tracking_params() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/p?utm_source=x&amp;utm_medium=y">P</a><a href="/p">P</a><a href="/p?id=1&amp;fbclid=abc">P</a></body></html>"#)
+                }),
+            )
+            .route("/p", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let sitemap = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(sitemap),
+            [
+                url.clone(),
+                url.join("/p").unwrap(),
+                url.join("/p?id=1").unwrap()
+            ]
+        );
+
+        let config = CrawlConfig {
+            tracking_params: vec![],
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(sitemap.len(), 4);
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_canonical_links() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><head><link rel="canonical" href="/"></head><body><a href="/dup">Duplicate</a></body></html>"#)
+                }),
+            )
+            .route(
+                "/dup",
+                get(|| async {
+                    Html(r#"<html><head><link rel="canonical" href="/"></head><body><a href="/page">Page</a></body></html>"#)
+                }),
+            )
+            .route("/page", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            use_canonical_links: true,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(
+            sorted_urls(sitemap),
+            [url.clone(), url.join("/page").unwrap()]
+        );
+
+        let sitemap = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        pretty_assertions::assert_eq!(sitemap.len(), 3);
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_canonical_page_fetched_last() {
+        const CANONICAL: &str = r#"<html><head><link rel="canonical" href="/canonical"></head><body>Original</body></html>"#;
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/canonical">Original</a><a href="/dup">Duplicate</a></body></html>"#)
+                }),
+            )
+            .route(
+                "/canonical",
+                get(|| async {
+                    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                    Html(CANONICAL)
+                }),
+            )
+            .route(
+                "/dup",
+                get(|| async {
+                    Html(r#"<html><head><link rel="canonical" href="/canonical"></head><body>Copy</body></html>"#)
+                }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            use_canonical_links: true,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(sitemap.len(), 2);
+        let page = &sitemap.pages[1];
+        pretty_assertions::assert_eq!(page.url, url.join("/canonical").unwrap());
+        pretty_assertions::assert_eq!(
+            page.md5_hash,
+            Some(format!("{:x}", md5::compute(CANONICAL)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_noindex() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/hidden">Hidden</a><a href="/tagged">Tagged</a></body></html>"#)
+                }),
+            )
+            .route(
+                "/hidden",
+                get(|| async {
+                    Html(r#"<html><head><meta name="robots" content="noindex, follow"></head><body><a href="/page">Page</a></body></html>"#)
+                }),
+            )
+            .route(
+                "/tagged",
+                get(|| async { ([("x-robots-tag", "noindex")], Html("<html><body></body></html>")) }),
+            )
+            .route("/page", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let sitemap = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(sitemap),
+            [url.clone(), url.join("/page").unwrap()]
+        );
+
+        let config = CrawlConfig {
+            respect_noindex: false,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(sitemap.len(), 4);
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_progress_callback() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/1">1</a><a href="/2">2</a></body></html>"#)
+                }),
+            )
+            .route("/1", get(|| async { Html("<html><body></body></html>") }))
+            .route("/2", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let progress = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let config = CrawlConfig {
+            on_progress: Some(ProgressCallback::new({
+                let progress = progress.clone();
+                move |p: CrawlProgress| {
+                    progress.lock().unwrap().push(p.clone());
+                    if p.pages_crawled == 1 {
+                        panic!("progress callback failure");
+                    }
+                }
+            })),
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
+
+        let progress = progress.lock().unwrap();
+        pretty_assertions::assert_eq!(
+            progress.iter().map(|p| p.pages_crawled).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        let mut crawled = progress.iter().map(|p| p.url.clone()).collect::<Vec<_>>();
+        crawled.sort();
+        pretty_assertions::assert_eq!(crawled, sorted_urls(sitemap));
+    }
+
+    #[tokio::test]
+    async fn test_multiple_seeds() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/about">About</a></body></html>"#) }),
+            )
+            .route(
+                "/about",
+                get(|| async { Html("<html><body></body></html>") }),
+            )
+            .route(
+                "/help",
+                get(|| async { Html(r#"<html><body><a href="/help/faq">FAQ</a></body></html>"#) }),
+            )
+            .route(
+                "/help/faq",
+                get(|| async { Html(r#"<html><body><a href="/help">Help</a></body></html>"#) }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let seeds = [url.clone(), url.join("/help").unwrap(), url.clone()];
+        let report = Sitemap::generate_by_crawling_multi(&seeds, &CrawlConfig::default())
+            .await
+            .unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [
+                url.clone(),
+                url.join("/about").unwrap(),
+                url.join("/help").unwrap(),
+                url.join("/help/faq").unwrap(),
+            ]
+        );
+
+        let seeds = [url.clone(), Url::parse("https://example.com/").unwrap()];
+        assert!(
+            Sitemap::generate_by_crawling_multi(&seeds, &CrawlConfig::default())
+                .await
+                .is_err()
+        );
+        assert!(
+            Sitemap::generate_by_crawling_multi(&[], &CrawlConfig::default())
+                .await
+                .is_err()
+        );
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_errors() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="http://[::1">Broken</a><a href="/page">Page</a></body></html>"#)
+                }),
+            )
+            .route("/page", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let report =
+            Sitemap::generate_by_crawling_with_config(url.clone(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/page").unwrap()]
+        );
+        pretty_assertions::assert_eq!(report.errors.len(), 1);
+        pretty_assertions::assert_eq!(report.errors[0].0, "http://[::1");
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_seed() {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        drop(listener);
+
+        let error = Sitemap::generate_by_crawling(url).await.unwrap_err();
+        assert!(Sitemap::is_unreachable_error(&error), "{}", error);
+        assert!(!Sitemap::is_unreachable_error("invalid URL"));
+
+        // A reachable website without listable pages is not an error.
+        let app = Router::new().route(
+            "/",
+            get(|| async {
+                Html(r#"<html><head><meta name="robots" content="noindex"></head></html>"#)
+            }),
+        );
+        let (url, tx) = serve(app).await;
+        let sitemap = Sitemap::generate_by_crawling(url).await.unwrap();
+        let _ = tx.send(());
+        assert!(sitemap.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_seed_sitemap() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/about">About</a></body></html>"#) }),
+            )
+            .route("/about", get(|| async { Html("<html><body></body></html>") }))
+            .route("/hidden", get(|| async { Html("<html><body>Hidden</body></html>") }))
+            .route(
+                "/sitemap.xml",
+                get(|headers: axum::http::HeaderMap| async move {
+                    let host = headers[axum::http::header::HOST].to_str().unwrap();
+                    let xml = format!(
+                        r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"><url><loc>http://{}/hidden</loc></url><url><loc>https://example.com/</loc></url></urlset>"#,
+                        host
+                    );
+                    ([(axum::http::header::CONTENT_TYPE, "application/xml")], xml)
+                }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let report =
+            Sitemap::generate_by_crawling_with_config(url.clone(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/about").unwrap()]
+        );
+
+        let config = CrawlConfig {
+            seed_sitemap: Some(url.join("/sitemap.xml").unwrap()),
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(
+            sitemap.urls(),
+            [
+                &url,
+                &url.join("/about").unwrap(),
+                &url.join("/hidden").unwrap(),
+            ]
+        );
+        let hidden = &sitemap.pages[2];
+        pretty_assertions::assert_eq!(
+            hidden.md5_hash,
+            Some(HashMode::Raw.fingerprint("<html><body>Hidden</body></html>"))
+        );
+
+        let config = CrawlConfig {
+            seed_sitemap: Some(url.join("/missing.xml").unwrap()),
+            ..Default::default()
+        };
+        assert!(Sitemap::generate_by_crawling_with_config(url, &config)
+            .await
+            .is_err());
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_nofollow() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/b">B</a><a rel="external nofollow" href="/c">C</a></body></html>"#)
+                }),
+            )
+            .route("/b", get(|| async { Html("<html><body></body></html>") }))
+            .route("/c", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let sitemap = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        pretty_assertions::assert_eq!(sitemap.len(), 3);
+
+        let config = CrawlConfig {
+            respect_nofollow: true,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(sorted_urls(sitemap), [url.clone(), url.join("/b").unwrap()]);
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_path_prefix() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/docs">Docs</a></body></html>"#) }),
+            )
+            .route(
+                "/docs",
+                get(|| async {
+                    Html(r#"<html><body><a href="/docs/intro">Intro</a><a href="/blog">Blog</a><a href="/docs-old">Old docs</a></body></html>"#)
+                }),
+            )
+            .route(
+                "/docs/intro",
+                get(|| async { Html(r#"<html><body><a href="/">Home</a></body></html>"#) }),
+            )
+            .route("/blog", get(|| async { Html("<html><body></body></html>") }))
+            .route("/docs-old", get(|| async { Html("<html><body></body></html>") }));
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            path_prefix: Some("/docs".to_string()),
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.join("/docs").unwrap(), &config)
+            .await
+            .unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.join("/docs").unwrap(), url.join("/docs/intro").unwrap()]
+        );
+        pretty_assertions::assert_eq!(
+            report.skipped,
+            [
+                (url.clone(), SkipReason::Excluded),
+                (url.join("/blog").unwrap(), SkipReason::Excluded),
+                (url.join("/docs-old").unwrap(), SkipReason::Excluded),
+            ]
+        );
+        let _ = tx.send(());
+    }
+
+    async fn protected_page(headers: axum::http::HeaderMap) -> axum::response::Response {
+        use axum::response::IntoResponse;
+        if headers.get("authorization").map(|value| value.as_bytes()) != Some(b"Bearer secret") {
+            return axum::http::StatusCode::UNAUTHORIZED.into_response();
+        }
+        Html(r#"<html><body><a href="/page">Page</a></body></html>"#).into_response()
+    }
+
+    #[tokio::test]
+    async fn test_headers() {
+        let app = Router::new()
+            .route("/", get(protected_page))
+            .route("/page", get(protected_page))
+            .route(
+                "/sitemap.xml",
+                get(|headers: axum::http::HeaderMap| async move {
+                    if headers.contains_key("authorization") {
+                        Ok(include_str!("data/simple-sitemap.xml"))
+                    } else {
+                        Err(axum::http::StatusCode::UNAUTHORIZED)
+                    }
+                }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let report =
+            Sitemap::generate_by_crawling_with_config(url.clone(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        assert!(report.sitemap.is_empty());
+        pretty_assertions::assert_eq!(report.error_pages, [(url.clone(), 401)]);
+
+        let config = CrawlConfig {
+            headers: vec![("Authorization".to_string(), "Bearer secret".to_string())],
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        pretty_assertions::assert_eq!(
+            sorted_urls(sitemap),
+            [url.clone(), url.join("/page").unwrap()]
+        );
+        assert!(!format!("{:?}", config).contains("secret"));
+
+        let sitemap_url = url.join("/sitemap.xml").unwrap();
+        assert!(Sitemap::import(sitemap_url.clone()).await.is_err());
+        let sitemap = Sitemap::import_with_config(sitemap_url.clone(), &config)
+            .await
+            .unwrap();
+        assert!(!sitemap.is_empty());
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+        let imported = Sitemap::import_with_client(sitemap_url, &client)
+            .await
+            .unwrap();
+        pretty_assertions::assert_eq!(imported, sitemap);
+        let _ = tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_headers_not_sent_to_other_origins() {
+        use axum::response::Redirect;
+
+        let leaked = std::sync::Arc::new(AtomicUsize::new(0));
+        let other = Router::new().fallback({
+            let leaked = leaked.clone();
+            move |headers: axum::http::HeaderMap| async move {
+                if headers.contains_key("x-api-key") {
+                    leaked.fetch_add(1, Ordering::SeqCst);
+                }
+                include_str!("data/simple-sitemap.xml")
+            }
+        });
+        let (other_url, other_tx) = serve(other).await;
+
+        let away = other_url.join("/away").unwrap().to_string();
+        let sitemap = other_url.join("/sitemap.xml").unwrap().to_string();
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/away">Away</a></body></html>"#) }),
+            )
+            .route(
+                "/away",
+                get(move || async move { Redirect::temporary(&away) }),
+            )
+            .route(
+                "/sitemap.xml",
+                get(move || async move { Redirect::temporary(&sitemap) }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            headers: vec![("X-Api-Key".to_string(), "secret".to_string())],
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        pretty_assertions::assert_eq!(sorted_urls(report.sitemap), std::slice::from_ref(&url));
+        pretty_assertions::assert_eq!(
+            report.redirects,
+            [(url.join("/away").unwrap(), other_url.join("/away").unwrap())]
+        );
+
+        let error = Sitemap::import_with_config(url.join("/sitemap.xml").unwrap(), &config)
+            .await
+            .unwrap_err();
+        assert!(error.ends_with("redirected to another origin"));
+        pretty_assertions::assert_eq!(leaked.load(Ordering::SeqCst), 0);
+
+        // Without custom headers, there is nothing to leak, so redirects are followed.
+        let sitemap =
+            Sitemap::import_with_config(url.join("/sitemap.xml").unwrap(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        assert!(!sitemap.is_empty());
+        let _ = tx.send(());
+        let _ = other_tx.send(());
+    }
+
+    #[tokio::test]
+    async fn test_fragments() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(
+                        r##"<html><body>
+                        <a href="#top">Top</a>
+                        <a href="/page#section">Section</a>
+                        <a href="/page">Page</a>
+                        </body></html>"##,
+                    )
+                }),
+            )
+            .route(
+                "/page",
+                get(|| async { Html("<html><body></body></html>") }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let sitemap = Sitemap::generate_by_crawling(url.join("/#intro").unwrap())
+            .await
+            .unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(sitemap),
+            [url.clone(), url.join("/page").unwrap()]
+        );
+
+        let config = CrawlConfig {
+            keep_fragments: true,
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
+        pretty_assertions::assert_eq!(
+            sorted_urls(sitemap),
+            ["/", "/#top", "/page", "/page#section"].map(|path| url.join(path).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_from_website() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/about">About</a></body></html>"#) }),
+            )
+            .route(
+                "/about",
+                get(|| async { Html("<html><body></body></html>") }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let mut website = Website::new(url.as_str());
+        website.with_respect_robots_txt(false);
+        let sitemap = Sitemap::generate_from_website(website).await.unwrap();
+        let _ = tx.send(());
+
+        assert!(sitemap
+            .pages
+            .iter()
+            .all(|page| page.lastmod.is_some() && page.md5_hash.is_some()));
+        pretty_assertions::assert_eq!(
+            sitemap.into_urls(),
+            [url.clone(), url.join("/about").unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_base_href() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(
+                        r#"<html><head><base href="/app/"></head><body>
+                        <a href="settings">Settings</a>
+                        <a href="../about">About</a>
+                        <a href="/contact">Contact</a>
+                        </body></html>"#,
+                    )
+                }),
+            )
+            .route(
+                "/app/settings",
+                get(|| async { Html("<html><body></body></html>") }),
+            )
+            .route(
+                "/about",
+                get(|| async { Html("<html><body></body></html>") }),
+            )
+            .route(
+                "/contact",
+                get(|| async { Html("<html><body></body></html>") }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let report =
+            Sitemap::generate_by_crawling_with_config(url.clone(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        let _ = tx.send(());
+        pretty_assertions::assert_eq!(report.error_pages, []);
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            ["/", "/about", "/app/settings", "/contact"].map(|path| url.join(path).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth() {
+        async fn check(headers: axum::http::HeaderMap) -> Result<(), axum::http::StatusCode> {
+            match headers.get("authorization") {
+                Some(value) if value == "Basic dXNlcjpwQHNz" => Ok(()),
+                _ => Err(axum::http::StatusCode::UNAUTHORIZED),
+            }
+        }
+        let app = Router::new()
+            .route(
+                "/",
+                get(|headers| async move {
+                    check(headers).await?;
+                    Ok::<_, axum::http::StatusCode>(Html(
+                        r#"<html><body><a href="/page">Page</a></body></html>"#,
+                    ))
+                }),
+            )
+            .route(
+                "/page",
+                get(|headers| async move {
+                    check(headers).await?;
+                    Ok::<_, axum::http::StatusCode>(Html("<html><body></body></html>"))
+                }),
+            )
+            .route(
+                "/sitemap.xml",
+                get(|headers| async move {
+                    check(headers).await?;
+                    Ok::<_, axum::http::StatusCode>(include_str!("data/simple-sitemap.xml"))
+                }),
+            )
+            .route(
+                "/robots.txt",
+                get(|headers| async move {
+                    check(headers).await?;
+                    Ok::<_, axum::http::StatusCode>("User-agent: *\nDisallow: /page\n")
+                }),
+            );
+        let (url, tx) = serve(app).await;
 
-    let str_representation_trimmed = include_str!("data/simple-sitemap-trimmed.xml")
-        .trim()
-        .replace("\r\n", "\n");
+        let mut seed = url.clone();
+        seed.set_username("user").unwrap();
+        seed.set_password(Some("p@ss")).unwrap();
+        let report =
+            Sitemap::generate_by_crawling_with_config(seed.clone(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        assert!(report.error_pages.is_empty());
+        let xml = report.sitemap.to_xml_string().unwrap();
+        assert!(!xml.contains("user") && !xml.contains("ss@"));
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/page").unwrap()]
+        );
 
-    pretty_assertions::assert_eq!(deserialized_from_original, sitemap);
-    pretty_assertions::assert_eq!(serialized, str_representation_trimmed);
-    pretty_assertions::assert_eq!(deserialized_from_rust, sitemap);
-}
+        // `robots.txt` is fetched with the credentials too.
+        let config = CrawlConfig {
+            respect_robots_txt: true,
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(seed.clone(), &config)
+            .await
+            .unwrap();
+        pretty_assertions::assert_eq!(sorted_urls(report.sitemap), std::slice::from_ref(&url));
+        pretty_assertions::assert_eq!(
+            report.skipped,
+            [(url.join("/page").unwrap(), SkipReason::RobotsDisallowed)]
+        );
 
-mod sitemap {
-    use super::*;
-    use axum::response::Html;
-    use axum::{routing::get, Router};
-    use std::net::SocketAddr;
+        let sitemap = Sitemap::import(seed.join("/sitemap.xml").unwrap())
+            .await
+            .unwrap();
+        let _ = tx.send(());
+        assert!(!sitemap.is_empty());
+    }
+
+    static PROXIED_REQUESTS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(vec![]);
 
     #[tokio::test]
-    async fn test_generation_and_update() {
-        let start_time = chrono::Utc::now();
+    async fn test_proxy() {
+        // Plain HTTP requests reach the proxy with the full target URL.
+        let proxy = Router::new().fallback(|uri: axum::http::Uri| async move {
+            PROXIED_REQUESTS.lock().unwrap().push(uri.to_string());
+            Html(r#"<html><body><a href="/page">Page</a></body></html>"#)
+        });
+        let (proxy_url, tx) = serve(proxy).await;
 
-        let (mut new_sitemap, mut old_sitemap) = obtain_sitemaps().await.unwrap();
+        let config = CrawlConfig {
+            proxy: Some(proxy_url),
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config("http://proxied.invalid/", &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
 
-        let correct_urls = vec![
-            Url::parse("http://localhost:3000/").unwrap(),
-            Url::parse("http://localhost:3000/a").unwrap(),
-            Url::parse("http://localhost:3000/b").unwrap(),
-            Url::parse("http://localhost:3000/c").unwrap(),
-            // Shouldn't be reachable by crawling:
-            // Url::parse("http://localhost:3000/d").unwrap(),
-        ];
+        pretty_assertions::assert_eq!(sitemap.len(), 2);
+        assert!(PROXIED_REQUESTS
+            .lock()
+            .unwrap()
+            .contains(&"http://proxied.invalid/page".to_string()));
 
-        for (page, correct_url) in new_sitemap.pages.iter().zip(correct_urls.iter()) {
-            pretty_assertions::assert_eq!(page.url, correct_url.clone());
+        let config = CrawlConfig {
+            proxy: Some(Url::parse("ftp://127.0.0.1:21").unwrap()),
+            ..Default::default()
+        };
+        assert!(
+            Sitemap::generate_by_crawling_with_config("http://proxied.invalid/", &config)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_max_duration() {
+        async fn page(axum::extract::Path(n): axum::extract::Path<u32>) -> Html<String> {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            Html(format!(
+                r#"<html><body><a href="/{}">Next</a></body></html>"#,
+                n + 1
+            ))
         }
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/1">1</a></body></html>"#) }),
+            )
+            .route("/:n", get(page));
+        let (url, tx) = serve(app).await;
 
-        old_sitemap.update_domain("http://localhost:3000").unwrap();
-        let info = new_sitemap.combine_with_old_sitemap(&old_sitemap).unwrap();
-        new_sitemap.update_domain("https://example.com").unwrap();
+        let config = CrawlConfig {
+            max_duration: Some(std::time::Duration::from_millis(350)),
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        let _ = tx.send(());
 
-        let end_time = chrono::Utc::now();
+        more_asserts::assert_lt!(start.elapsed(), std::time::Duration::from_secs(2));
+        assert!(report.partial);
+        more_asserts::assert_ge!(report.sitemap.pages.len(), 2);
+        pretty_assertions::assert_eq!(report.sitemap.pages[0].url, url);
+    }
 
-        let correct_urls = vec![
-            Url::parse("https://example.com/").unwrap(),
-            Url::parse("https://example.com/a").unwrap(),
-            Url::parse("https://example.com/b").unwrap(),
-            Url::parse("https://example.com/c").unwrap(),
-            // Shouldn't be reachable by crawling:
-            // Url::parse("http://localhost:3000/d").unwrap(),
-        ];
-        let updated_urls = vec![
-            Url::parse("https://example.com/a").unwrap(),
-            Url::parse("https://example.com/b").unwrap(),
-            Url::parse("https://example.com/c").unwrap(),
-        ];
-        for (page, correct_url) in new_sitemap.pages.iter().zip(correct_urls.iter()) {
-            pretty_assertions::assert_eq!(page.url, correct_url.clone());
-            let lastmod = page.lastmod.unwrap();
+    #[tokio::test]
+    async fn test_import_index() {
+        let urlset = |paths: &[&str]| {
+            let pages = paths
+                .iter()
+                .map(|path| {
+                    Page::new(
+                        Url::parse("https://example.com/")
+                            .unwrap()
+                            .join(path)
+                            .unwrap(),
+                    )
+                })
+                .collect();
+            Sitemap { pages }.to_xml_string().unwrap()
+        };
+        let first = urlset(&["/", "/a"]);
+        let second = urlset(&["/a", "/b"]);
+        let app = Router::new()
+            .route("/sitemap-1.xml", get(|| async move { first }))
+            .route("/sitemap-2.xml", get(|| async move { second }));
+        let (url, tx) = serve(app).await;
 
-            if updated_urls.contains(&page.url) {
-                more_asserts::assert_lt!(start_time, lastmod);
-                more_asserts::assert_lt!(lastmod, end_time);
-            } else {
-                more_asserts::assert_lt!(lastmod, start_time);
-            }
-        }
+        let index = SitemapIndex {
+            sitemaps: ["sitemap-1.xml", "missing.xml", "sitemap-2.xml"]
+                .iter()
+                .map(|path| SitemapIndexEntry {
+                    loc: url.join(path).unwrap(),
+                    lastmod: None,
+                })
+                .collect(),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sitemap-index.xml");
+        let mut buf = vec![];
+        index.serialize(&mut buf).unwrap();
+        std::fs::write(&path, buf).unwrap();
+        let path = path.to_str().unwrap();
 
-        let correct_info = UpdateInfo {
-            new_pages: vec![Url::parse("http://localhost:3000/c").unwrap()],
-            removed_pages: vec![
-                Url::parse("http://localhost:3000/old-nonexistent-page-with-hash").unwrap(),
-                Url::parse("http://localhost:3000/old-nonexistent-page-without-hash").unwrap(),
-            ],
-            unchanged_pages: vec![Url::parse("http://localhost:3000/").unwrap()],
-            updated_pages: vec![
-                Url::parse("http://localhost:3000/a").unwrap(),
-                Url::parse("http://localhost:3000/b").unwrap(),
-            ],
+        let report = Sitemap::import_with_options(path, &ImportOptions::default())
+            .await
+            .unwrap();
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [
+                "https://example.com/",
+                "https://example.com/a",
+                "https://example.com/b"
+            ]
+            .map(|url| Url::parse(url).unwrap())
+        );
+        pretty_assertions::assert_eq!(report.errors.len(), 1);
+        pretty_assertions::assert_eq!(report.errors[0].0, url.join("missing.xml").unwrap());
+        assert!(report.not_fetched.is_empty());
+
+        let options = ImportOptions {
+            max_child_sitemaps: 1,
         };
-        pretty_assertions::assert_eq!(info, correct_info);
+        let report = Sitemap::import_with_options(path, &options).await.unwrap();
+        let _ = tx.send(());
+        pretty_assertions::assert_eq!(report.sitemap.pages.len(), 2);
+        assert!(report.errors.is_empty());
+        pretty_assertions::assert_eq!(
+            report.not_fetched,
+            [
+                url.join("missing.xml").unwrap(),
+                url.join("sitemap-2.xml").unwrap()
+            ]
+        );
     }
 
-    async fn obtain_sitemaps() -> Result<(Sitemap, Sitemap), String> {
+    #[tokio::test]
+    async fn test_import_non_xml() {
+        let sitemap = Sitemap {
+            pages: vec![Page::new(Url::parse("https://example.com/").unwrap())],
+        };
+        let xml = sitemap.to_xml_string().unwrap();
         let app = Router::new()
-            .route("/", get(root))
-            .route("/a", get(a))
-            .route("/b", get(b))
-            .route("/c", get(c))
-            .route("/d", get(d))
-            .route("/sitemap.xml", get(sitemap));
+            .route(
+                "/sitemap.xml",
+                get(|| async { Html("<!DOCTYPE html><html><body>Not found</body></html>") }),
+            )
+            .route("/plain.xml", get(|| async move { xml }));
+        let (url, tx) = serve(app).await;
 
-        let port = 3000;
-        let addr = SocketAddr::from(([127, 0, 0, 1], port));
-        let server = axum::Server::bind(&addr).serve(app.into_make_service());
+        let result = Sitemap::import(url.join("/sitemap.xml").unwrap()).await;
+        pretty_assertions::assert_eq!(
+            result,
+            Err(format!(
+                "failed to get {}sitemap.xml: expected XML sitemap, got text/html",
+                url
+            ))
+        );
 
-        // Prepare some signal for when the server should start shutting down...
-        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
-        let graceful = server.with_graceful_shutdown(async {
-            rx.await.ok();
-        });
+        let imported = Sitemap::import(url.join("/plain.xml").unwrap())
+            .await
+            .unwrap();
+        let _ = tx.send(());
+        pretty_assertions::assert_eq!(imported, sitemap);
+    }
 
-        println!("Listening on http://localhost:{port}");
-        tokio::spawn(async {
-            if let Err(e) = graceful.await {
-                eprintln!("server error: {}", e);
-            }
-        });
+    #[tokio::test]
+    async fn test_cancellation() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/1">1</a><a href="/slow">Slow</a></body></html>"#)
+                }),
+            )
+            .route("/1", get(|| async { Html("<html><body></body></html>") }))
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                    Html("<html><body></body></html>")
+                }),
+            );
+        let (url, tx) = serve(app).await;
 
-        let mut new_sitemap = Sitemap::generate_by_crawling("http://localhost:3000").await?;
-        new_sitemap.sort_by_url();
+        let cancellation = CancellationToken::new();
+        let config = CrawlConfig {
+            on_progress: Some(ProgressCallback::new({
+                let cancellation = cancellation.clone();
+                move |p: CrawlProgress| {
+                    if p.url.path() == "/1" {
+                        cancellation.cancel();
+                    }
+                }
+            })),
+            cancellation: Some(cancellation),
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        let _ = tx.send(());
 
-        // Try with `url::Url` instead of `&str`:
-        let old_sitemap =
-            Sitemap::import(Url::parse("http://localhost:3000/sitemap.xml").unwrap()).await?;
+        more_asserts::assert_lt!(start.elapsed(), std::time::Duration::from_secs(5));
+        assert!(report.partial);
+        assert!(report.sitemap.validate().is_ok());
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/1").unwrap()]
+        );
+    }
 
-        // Shut down the server...
+    #[tokio::test]
+    async fn test_lastmod_on_hash_change() {
+        let visits = std::sync::Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/news">News</a></body></html>"#) }),
+            )
+            .route(
+                "/news",
+                get({
+                    let visits = visits.clone();
+                    move || async move {
+                        let n = visits.fetch_add(1, Ordering::SeqCst);
+                        Html(format!("<html><body>{}</body></html>", n))
+                    }
+                }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let first_crawl = Utc.with_ymd_and_hms(2023, 8, 1, 0, 0, 0).unwrap();
+        let config = CrawlConfig {
+            crawl_time: Some(first_crawl),
+            ..Default::default()
+        };
+        let old_sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+
+        let second_crawl = Utc.with_ymd_and_hms(2023, 8, 2, 0, 0, 0).unwrap();
+        let config = CrawlConfig {
+            crawl_time: Some(second_crawl),
+            lastmod_policy: LastmodPolicy::OnHashChange(old_sitemap),
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
         let _ = tx.send(());
 
-        Ok((new_sitemap, old_sitemap))
+        let lastmods = sitemap
+            .pages
+            .iter()
+            .map(|page| (page.url.path(), page.lastmod.unwrap()))
+            .collect::<Vec<_>>();
+        pretty_assertions::assert_eq!(
+            lastmods,
+            [
+                ("/", first_crawl.fixed_offset()),
+                ("/news", second_crawl.fixed_offset())
+            ]
+        );
     }
 
-    // Reachable from / and /c.
-    // Also ensure it works on all OSs by importing a file, which may have \n or \r\n line endings.
-    async fn root() -> Html<&'static str> {
-        Html(include_str!("data/root.html"))
+    static FLAKY_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    async fn flaky_page() -> Result<Html<&'static str>, axum::http::StatusCode> {
+        // Fails twice out of every three requests.
+        if FLAKY_ATTEMPTS.fetch_add(1, Ordering::SeqCst) % 3 < 2 {
+            Err(axum::http::StatusCode::SERVICE_UNAVAILABLE)
+        } else {
+            Ok(Html("<html><body></body></html>"))
+        }
     }
 
-    // Reachable from /.
-    async fn a() -> Html<&'static str> {
-        Html(
-            r#"
-<html>
-  <body>
-    <a href="/b">Reachable from home and a</a>
-    <a href="/c">Reachable from home a</a>
-  </body>
-</html>
-"#,
-        )
+    static SLOW_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    async fn slow_first_page() -> Html<&'static str> {
+        // Only the first request takes longer than the timeout.
+        if SLOW_ATTEMPTS.fetch_add(1, Ordering::SeqCst) == 0 {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        }
+        Html("<html><body></body></html>")
     }
 
-    // Reachable from /home and /a.
-    async fn b() -> Html<&'static str> {
-        Html(
-            r#"
-<html>
-  <body>
-  </body>
-</html>
-"#,
+    #[tokio::test]
+    async fn test_retries() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/flaky">Flaky</a></body></html>"#) }),
+            )
+            .route("/flaky", get(flaky_page));
+        let (url, tx) = serve(app).await;
+
+        let report =
+            Sitemap::generate_by_crawling_with_config(url.clone(), &CrawlConfig::default())
+                .await
+                .unwrap();
+        pretty_assertions::assert_eq!(sorted_urls(report.sitemap), vec![url.clone()]);
+        pretty_assertions::assert_eq!(report.error_pages, [(url.join("/flaky").unwrap(), 503)]);
+        pretty_assertions::assert_eq!(FLAKY_ATTEMPTS.load(Ordering::SeqCst), 1);
+
+        FLAKY_ATTEMPTS.store(0, Ordering::SeqCst);
+        let config = CrawlConfig {
+            max_retries: 2,
+            retry_backoff: std::time::Duration::from_millis(10),
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        let _ = tx.send(());
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/flaky").unwrap()]
+        );
+        assert!(report.error_pages.is_empty());
+        pretty_assertions::assert_eq!(FLAKY_ATTEMPTS.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retries_after_timeout() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { Html(r#"<html><body><a href="/slow">Slow</a></body></html>"#) }),
+            )
+            .route("/slow", get(slow_first_page));
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            request_timeout: std::time::Duration::from_millis(200),
+            max_retries: 1,
+            retry_backoff: std::time::Duration::from_millis(10),
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/slow").unwrap()]
+        );
+        assert!(report.timed_out.is_empty());
+        pretty_assertions::assert_eq!(SLOW_ATTEMPTS.load(Ordering::SeqCst), 2);
+    }
+
+    static FULL_RESPONSES: AtomicUsize = AtomicUsize::new(0);
+
+    async fn cached_page(headers: axum::http::HeaderMap) -> axum::response::Response {
+        use axum::response::IntoResponse;
+        if headers.get("if-none-match").map(|value| value.as_bytes()) == Some(br#""v1""#) {
+            return axum::http::StatusCode::NOT_MODIFIED.into_response();
+        }
+        FULL_RESPONSES.fetch_add(1, Ordering::SeqCst);
+        (
+            [("etag", r#""v1""#)],
+            Html(r#"<html><body><a href="/a">A</a></body></html>"#),
         )
+            .into_response()
     }
 
-    // Reachable from /a and /c.
-    async fn c() -> Html<&'static str> {
-        Html(
-            r#"
-<html>
-  <body>
-    <a href="/c">Reachable from itself</a>
-    <a href="/root">Reachable from home and c</a>
-  </body>
-</html>
-"#,
+    static IF_MODIFIED_SINCE_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+    #[tokio::test]
+    async fn test_conditional_requests() {
+        let app = Router::new().route("/", get(cached_page)).route(
+            "/a",
+            get(|headers: axum::http::HeaderMap| async move {
+                if headers.contains_key("if-modified-since") {
+                    IF_MODIFIED_SINCE_REQUESTS.fetch_add(1, Ordering::SeqCst);
+                }
+                Html("<html><body></body></html>")
+            }),
+        );
+        let (url, tx) = serve(app).await;
+
+        let mut old_sitemap = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        old_sitemap.sort_by_url();
+        pretty_assertions::assert_eq!(old_sitemap.pages[0].etag.as_deref(), Some(r#""v1""#));
+        pretty_assertions::assert_eq!(FULL_RESPONSES.load(Ordering::SeqCst), 1);
+
+        let mut sitemap = Sitemap::generate_by_crawling_incremental(
+            url.clone(),
+            &CrawlConfig::default(),
+            &old_sitemap,
         )
+        .await
+        .unwrap()
+        .sitemap;
+        sitemap.sort_by_url();
+
+        // The unchanged page is downloaded again only to follow its links.
+        pretty_assertions::assert_eq!(FULL_RESPONSES.load(Ordering::SeqCst), 2);
+        // The old lastmod is the time of the crawl, not one from the server.
+        pretty_assertions::assert_eq!(IF_MODIFIED_SINCE_REQUESTS.load(Ordering::SeqCst), 0);
+        pretty_assertions::assert_eq!(sitemap.pages[0], old_sitemap.pages[0]);
+        pretty_assertions::assert_eq!(sitemap.pages[1].url, url.join("/a").unwrap());
+        pretty_assertions::assert_eq!(sitemap.pages[1].md5_hash, old_sitemap.pages[1].md5_hash);
+
+        let config = CrawlConfig {
+            lastmod_policy: LastmodPolicy::HttpHeader,
+            ..Default::default()
+        };
+        Sitemap::generate_by_crawling_incremental(url.clone(), &config, &old_sitemap)
+            .await
+            .unwrap();
+        let _ = tx.send(());
+        pretty_assertions::assert_eq!(IF_MODIFIED_SINCE_REQUESTS.load(Ordering::SeqCst), 1);
     }
 
-    // Unreachable.
-    async fn d() -> Html<&'static str> {
-        Html(
-            r#"
-            <html><body>
-                <h1>Unreachable!</h1>
-            </body></html>
-        "#,
+    #[tokio::test]
+    async fn test_incremental_crawl() {
+        let full_responses = std::sync::Arc::new(AtomicUsize::new(0));
+        let news_visits = std::sync::Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route(
+                "/",
+                get({
+                    let full_responses = full_responses.clone();
+                    move |headers: axum::http::HeaderMap| async move {
+                        use axum::response::IntoResponse;
+                        if headers.contains_key("if-none-match") {
+                            return axum::http::StatusCode::NOT_MODIFIED.into_response();
+                        }
+                        // The validator is not updated when `/new` is linked, so the page
+                        // is reported as not modified, yet the link is still followed.
+                        let new = if full_responses.fetch_add(1, Ordering::SeqCst) > 0 {
+                            r#"<a href="/new">New</a>"#
+                        } else {
+                            ""
+                        };
+                        (
+                            [("etag", r#""v1""#)],
+                            Html(format!(
+                                r#"<html><body><a href="/about">About</a><a href="/news">News</a>{}</body></html>"#,
+                                new
+                            )),
+                        )
+                            .into_response()
+                    }
+                }),
+            )
+            .route("/about", get(|| async { Html("<html><body></body></html>") }))
+            .route("/new", get(|| async { Html("<html><body></body></html>") }))
+            .route(
+                "/news",
+                get({
+                    let news_visits = news_visits.clone();
+                    move || async move {
+                        let n = news_visits.fetch_add(1, Ordering::SeqCst);
+                        Html(format!("<html><body>{}</body></html>", n))
+                    }
+                }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let first_crawl = Utc.with_ymd_and_hms(2023, 8, 1, 0, 0, 0).unwrap();
+        let config = CrawlConfig {
+            crawl_time: Some(first_crawl),
+            ..Default::default()
+        };
+        let old_sitemap = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+
+        let second_crawl = Utc.with_ymd_and_hms(2023, 8, 2, 0, 0, 0).unwrap();
+        let config = CrawlConfig {
+            crawl_time: Some(second_crawl),
+            ..Default::default()
+        };
+        let sitemap = Sitemap::generate_by_crawling_incremental(url.clone(), &config, &old_sitemap)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
+
+        // The home page keeps its old data, but is downloaded again for its links.
+        pretty_assertions::assert_eq!(full_responses.load(Ordering::SeqCst), 2);
+        pretty_assertions::assert_eq!(sitemap.pages[0], old_sitemap.pages[0]);
+        let lastmods = sitemap
+            .pages
+            .iter()
+            .map(|page| (page.url.path(), page.lastmod.unwrap()))
+            .collect::<Vec<_>>();
+        pretty_assertions::assert_eq!(
+            lastmods,
+            [
+                ("/", first_crawl.fixed_offset()),
+                ("/about", first_crawl.fixed_offset()),
+                ("/new", second_crawl.fixed_offset()),
+                ("/news", second_crawl.fixed_offset())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_content_length() {
+        let body = "<html><body><p>Grüße</p></body></html>";
+        let app = Router::new().route("/", get(move || async move { Html(body) }));
+        let (url, tx) = serve(app).await;
+
+        let sitemap = Sitemap::generate_by_crawling(url).await.unwrap();
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(sitemap.pages[0].content_length, Some(body.len()));
+        pretty_assertions::assert_eq!(sitemap.stats().total_bytes, body.len());
+    }
+
+    #[tokio::test]
+    async fn test_skipped_links() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(
+                        r#"<html><body>
+                        <a href="/docs">Docs</a>
+                        <a href="/blog">Blog</a>
+                        <a href="/docs/logo.png">Logo</a>
+                        <a href="/docs/private">Private</a>
+                        <a href="https://example.com/">External</a>
+                        <a href="mailto:info@example.com">Email</a>
+                    </body></html>"#,
+                    )
+                }),
+            )
+            .route(
+                "/docs",
+                get(|| async { Html(r#"<html><body><a href="/">Home</a></body></html>"#) }),
+            )
+            .route(
+                "/robots.txt",
+                get(|| async { "User-agent: *\nDisallow: /docs/private\n" }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            respect_robots_txt: true,
+            path_prefix: Some("/docs".to_string()),
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [url.clone(), url.join("/docs").unwrap()]
+        );
+        pretty_assertions::assert_eq!(
+            report.skipped,
+            [
+                (url.join("/blog").unwrap(), SkipReason::Excluded),
+                (url.join("/docs/logo.png").unwrap(), SkipReason::Excluded),
+                (
+                    url.join("/docs/private").unwrap(),
+                    SkipReason::RobotsDisallowed
+                ),
+                (
+                    Url::parse("https://example.com/").unwrap(),
+                    SkipReason::External
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_max_depth() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    Html(r#"<html><body><a href="/a">A</a><a href="/a/b">B</a></body></html>"#)
+                }),
+            )
+            .route(
+                "/a",
+                get(|| async { Html(r#"<html><body><a href="/a/b/c">C</a></body></html>"#) }),
+            )
+            .route("/a/b", get(|| async { Html("<html><body></body></html>") }))
+            .route(
+                "/a/b/c",
+                get(|| async { Html("<html><body></body></html>") }),
+            );
+        let (url, tx) = serve(app).await;
+
+        let config = CrawlConfig {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let report = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap();
+        let _ = tx.send(());
+
+        // `/a/b` is also linked directly from the seed, so it is within the limit.
+        pretty_assertions::assert_eq!(
+            sorted_urls(report.sitemap),
+            [
+                url.clone(),
+                url.join("/a").unwrap(),
+                url.join("/a/b").unwrap()
+            ]
+        );
+        pretty_assertions::assert_eq!(
+            report.skipped,
+            [(url.join("/a/b/c").unwrap(), SkipReason::OverDepth)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_order() {
+        async fn page() -> Html<&'static str> {
+            tokio::time::sleep(std::time::Duration::from_millis(next_delay())).await;
+            Html(
+                r#"<html><body><a href="/1">1</a><a href="/2">2</a><a href="/3">3</a></body></html>"#,
+            )
+        }
+        // Varies the order in which responses arrive.
+        fn next_delay() -> u64 {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            [30, 5, 20, 10][COUNTER.fetch_add(1, Ordering::SeqCst) % 4]
+        }
+        let app = Router::new()
+            .route("/", get(page))
+            .route("/1", get(page))
+            .route("/2", get(page))
+            .route("/3", get(page));
+        let (url, tx) = serve(app).await;
+
+        let first = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        let mut second = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        let _ = tx.send(());
+        second.combine_with_old_sitemap(&first).unwrap();
+
+        pretty_assertions::assert_eq!(
+            second.to_xml_string().unwrap(),
+            first.to_xml_string().unwrap()
+        );
+        pretty_assertions::assert_eq!(first.pages, {
+            let mut pages = first.pages.clone();
+            pages.sort_by(|a, b| a.url.cmp(&b.url));
+            pages
+        });
+    }
+
+    fn numbered_page(n: usize) -> String {
+        format!(
+            "<html><body><p>Page {n}</p>{}</body></html>",
+            "x".repeat(n * 100)
         )
     }
 
-    // Sitemap.
-    async fn sitemap() -> &'static str {
-        include_str!("data/old-sitemap.xml")
+    #[tokio::test]
+    async fn test_hashing_many_pages() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    let links: String = (0..60)
+                        .map(|n| format!(r#"<a href="/p/{n}">{n}</a>"#))
+                        .collect();
+                    Html(format!("<html><body>{links}</body></html>"))
+                }),
+            )
+            .route(
+                "/p/:n",
+                get(
+                    |axum::extract::Path(n): axum::extract::Path<usize>| async move {
+                        Html(numbered_page(n))
+                    },
+                ),
+            );
+        let (url, tx) = serve(app).await;
+
+        let parallel = Sitemap::generate_by_crawling(url.clone()).await.unwrap();
+        let config = CrawlConfig {
+            concurrency: 1,
+            ..Default::default()
+        };
+        let sequential = Sitemap::generate_by_crawling_with_config(url.clone(), &config)
+            .await
+            .unwrap()
+            .sitemap;
+        let _ = tx.send(());
+
+        pretty_assertions::assert_eq!(parallel.len(), 61);
+        for page in parallel.iter().filter(|page| page.url.path() != "/") {
+            let n: usize = page.url.path().trim_start_matches("/p/").parse().unwrap();
+            pretty_assertions::assert_eq!(
+                page.md5_hash,
+                Some(HashMode::Raw.fingerprint(&numbered_page(n)))
+            );
+        }
+        let hashes = |sitemap: &Sitemap| {
+            sitemap
+                .iter()
+                .map(|page| (page.url.clone(), page.md5_hash.clone()))
+                .collect::<Vec<_>>()
+        };
+        pretty_assertions::assert_eq!(hashes(&parallel), hashes(&sequential));
     }
 }